@@ -0,0 +1,208 @@
+use crate::{ChessMove, ChessPiece, PieceKind};
+
+/**
+ * Reasons [ChessMoveBuilder::build] can fail.
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum MoveBuildError {
+    /** The piece, origin or target was never set. */
+    MissingField,
+    /** The origin or target is not a square on the board. */
+    OutOfBounds,
+    /** The fields contradict each other, e.g. a castling promotion. */
+    Inconsistent,
+}
+
+/**
+ * Builds a [ChessMove] one field at a time, checking that the fields make
+ * sense together, for moves that are awkward to write as a struct literal:
+ *
+ * ```text
+ * let mv = ChessMoveBuilder::new()
+ *     .piece(ChessPiece::P(ChessColor::Wh))
+ *     .origin(50).target(59)
+ *     .captures(ChessPiece::R(ChessColor::Bl))
+ *     .promotes(ChessPiece::Q(ChessColor::Wh))
+ *     .build()?;
+ * ```
+ *
+ * This only checks the move on its own, not whether it is legal in any
+ * position.
+ */
+#[derive(Debug,Copy,Clone,Default,PartialEq,Eq)]
+pub struct ChessMoveBuilder {
+    piece: Option<ChessPiece>,
+    origin: Option<usize>,
+    target: Option<usize>,
+    captures: Option<ChessPiece>,
+    promotes: Option<ChessPiece>,
+    en_passant: bool,
+    castles: bool,
+}
+
+impl ChessMoveBuilder {
+    /**
+     * Creates a builder with no fields set.
+     */
+    pub fn new() -> ChessMoveBuilder {
+        return ChessMoveBuilder::default();
+    }
+
+    /**
+     * Sets the moving piece, see [ChessMove::piece].
+     */
+    pub fn piece(mut self, piece: ChessPiece) -> ChessMoveBuilder {
+        self.piece = Some(piece);
+        return self;
+    }
+
+    /**
+     * Sets the square the piece moves from, see [ChessMove::origin].
+     */
+    pub fn origin(mut self, origin: usize) -> ChessMoveBuilder {
+        self.origin = Some(origin);
+        return self;
+    }
+
+    /**
+     * Sets the square the piece moves to, see [ChessMove::target].
+     */
+    pub fn target(mut self, target: usize) -> ChessMoveBuilder {
+        self.target = Some(target);
+        return self;
+    }
+
+    /**
+     * Sets the captured piece, see [ChessMove::captures].
+     */
+    pub fn captures(mut self, captures: ChessPiece) -> ChessMoveBuilder {
+        self.captures = Some(captures);
+        return self;
+    }
+
+    /**
+     * Sets the piece a pawn promotes to, see [ChessMove::promotes].
+     */
+    pub fn promotes(mut self, promotes: ChessPiece) -> ChessMoveBuilder {
+        self.promotes = Some(promotes);
+        return self;
+    }
+
+    /**
+     * Marks the move as an en passant capture. The captured pawn is filled
+     * in if it isn't set.
+     */
+    pub fn en_passant(mut self) -> ChessMoveBuilder {
+        self.en_passant = true;
+        return self;
+    }
+
+    /**
+     * Marks the move as castling, given as the king's move.
+     */
+    pub fn castles(mut self) -> ChessMoveBuilder {
+        self.castles = true;
+        return self;
+    }
+
+    /**
+     * Creates the move. Fails with [MoveBuildError::MissingField] unless
+     * the piece, origin and target are set, [MoveBuildError::OutOfBounds] if
+     * a square is off the board, and [MoveBuildError::Inconsistent] if
+     * the fields can't describe one move: capturing an own piece or a king,
+     * promoting anything but a pawn or to a pawn, king or the other color,
+     * en passant with anything but pawns, or castling with anything but a
+     * king or combined with a capture or promotion.
+     */
+    pub fn build(self) -> Result<ChessMove, MoveBuildError> {
+        let (piece, origin, target) = match (self.piece, self.origin, self.target) {
+            (Some(piece), Some(origin), Some(target)) if piece != ChessPiece::None => {
+                (piece, origin, target)
+            },
+            _ => return Err(MoveBuildError::MissingField),
+        };
+        if origin >= 64 || target >= 64 {
+            return Err(MoveBuildError::OutOfBounds);
+        }
+
+        let col = piece.color().unwrap();
+        let captures = match self.captures {
+            Some(captures) => captures,
+            _ if self.en_passant => ChessPiece::P(col.opposite()),
+            _ => ChessPiece::None,
+        };
+        let promotes = self.promotes.unwrap_or(ChessPiece::None);
+
+        let consistent = captures.color() != Some(col)
+            && captures.kind() != Some(PieceKind::King)
+            && (promotes == ChessPiece::None || (piece.is_pawn()
+                && promotes.color() == Some(col)
+                && !matches!(promotes.kind(), Some(PieceKind::Pawn | PieceKind::King))))
+            && (!self.en_passant || (piece.is_pawn() && captures.is_pawn()))
+            && (!self.castles || (piece.kind() == Some(PieceKind::King)
+                && captures == ChessPiece::None
+                && promotes == ChessPiece::None
+                && !self.en_passant));
+        if !consistent {
+            return Err(MoveBuildError::Inconsistent);
+        }
+
+        return Ok(ChessMove {
+            piece, origin, target, captures, promotes,
+            en_passant: self.en_passant,
+            castles: self.castles,
+            annotation: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn build() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mv = ChessMoveBuilder::new().piece(P(Wh)).origin(12).target(28).build();
+        assert_eq!(mv, Ok(ChessMove::to(P(Wh), 12, 28)));
+
+        let mv = ChessMoveBuilder::new()
+            .piece(P(Wh))
+            .origin(50).target(59)
+            .captures(R(Bl))
+            .promotes(Q(Wh))
+            .build()
+            .unwrap();
+        assert_eq!((mv.captures, mv.promotes), (R(Bl), Q(Wh)));
+
+        let mv = ChessMoveBuilder::new().piece(P(Bl)).origin(27).target(20).en_passant().build();
+        assert_eq!(mv.map(|mv| (mv.captures, mv.en_passant)), Ok((P(Wh), true)));
+
+        let castle = ChessGame::new().mv_castle(&Bl, true);
+        let mv = ChessMoveBuilder::new().piece(K(Bl)).origin(60).target(58).castles().build();
+        assert_eq!(mv, Ok(castle));
+
+        let pawn = ChessMoveBuilder::new().piece(P(Wh)).origin(52).target(60);
+        assert_eq!(pawn.build().map(|mv| mv.promotes), Ok(None));
+        assert_eq!(pawn.promotes(N(Wh)).build().map(|mv| mv.promotes), Ok(N(Wh)));
+        assert_eq!(pawn.promotes(N(Bl)).build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(pawn.promotes(K(Wh)).build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(pawn.castles().build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(pawn.captures(P(Wh)).build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(pawn.captures(K(Bl)).build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(pawn.target(64).build(), Err(MoveBuildError::OutOfBounds));
+
+        let king = ChessMoveBuilder::new().piece(K(Wh)).origin(4).target(6).castles();
+        assert_eq!(king.promotes(Q(Wh)).build(), Err(MoveBuildError::Inconsistent));
+        assert_eq!(king.en_passant().build(), Err(MoveBuildError::Inconsistent));
+
+        assert_eq!(ChessMoveBuilder::new().origin(4).target(6).build(),
+                   Err(MoveBuildError::MissingField));
+        assert_eq!(ChessMoveBuilder::new().piece(None).origin(4).target(6).build(),
+                   Err(MoveBuildError::MissingField));
+        assert_eq!(ChessMoveBuilder::new().piece(K(Wh)).origin(4).build(),
+                   Err(MoveBuildError::MissingField));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{ChessColor, ChessGame, ChessPiece, PieceKind};
+
+const BOARD_BYTES: usize = 32;
+const FLAGS: usize = BOARD_BYTES;
+const EN_PASSANT: usize = FLAGS + 1;
+const HALFMOVE: usize = EN_PASSANT + 1;
+const FULLMOVE: usize = HALFMOVE + 4;
+const LEN: usize = FULLMOVE + 4;
+
+const NO_EN_PASSANT: u8 = 0xff;
+
+/* 0 is an empty square, 1-6 white pieces and 7-12 black pieces */
+fn encode(piece: &ChessPiece) -> u8 {
+    return match (piece.kind(), piece.color()) {
+        (Some(kind), Some(col)) => 1 + 6*(col as u8) + kind as u8,
+        _ => 0,
+    };
+}
+
+fn decode(nibble: u8) -> Option<ChessPiece> {
+    use PieceKind::*;
+
+    if nibble == 0 {
+        return Some(ChessPiece::None);
+    } else if nibble > 12 {
+        return None;
+    }
+    let col = if nibble > 6 { ChessColor::Bl } else { ChessColor::Wh };
+    let kind = [Pawn, Rook, Knight, Bishop, Queen, King][(nibble as usize - 1) % 6];
+    return Some(kind.with_color(col));
+}
+
+impl ChessGame {
+    /**
+     * Serializes the position into a compact binary form of 42 bytes: the
+     * board as one 4-bit nibble per square (low nibble first), followed by
+     * one byte of flags for the side to move and castling rights, the en
+     * passant target square (or `0xff`), and the halfmove clock and fullmove
+     * number as little endian [u32]s.
+     *
+     * Use [ChessGame::from_bytes] to restore the position.
+     */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0u8; LEN];
+
+        for (i, piece) in self.board.iter().enumerate() {
+            out[i / 2] |= encode(piece) << (4 * (i % 2));
+        }
+
+        out[FLAGS] = (self.side_to_move() == ChessColor::Bl) as u8
+            | (self.can_castle_k[ChessColor::Wh.index()] as u8) << 1
+            | (self.can_castle_q[ChessColor::Wh.index()] as u8) << 2
+            | (self.can_castle_k[ChessColor::Bl.index()] as u8) << 3
+            | (self.can_castle_q[ChessColor::Bl.index()] as u8) << 4;
+        out[EN_PASSANT] = match self.en_passant_target() {
+            Some(target) => target as u8,
+            _ => NO_EN_PASSANT,
+        };
+        out[HALFMOVE..FULLMOVE].copy_from_slice(&self.halfmove_clock.to_le_bytes());
+        out[FULLMOVE..LEN].copy_from_slice(&self.fullmove_number.to_le_bytes());
+
+        return out;
+    }
+
+    /**
+     * Restores a position serialized with [ChessGame::to_bytes]. Returns
+     * [None] if `bytes` is not a valid serialized position.
+     */
+    pub fn from_bytes(bytes: &[u8]) -> Option<ChessGame> {
+        if bytes.len() != LEN || bytes[FLAGS] >> 5 != 0 {
+            return None;
+        }
+
+        let mut game = ChessGame::new();
+        for i in 0..64 {
+            game.board[i] = decode((bytes[i / 2] >> (4 * (i % 2))) & 0xf)?;
+        }
+
+        let flags = bytes[FLAGS];
+        let turn = if flags & 1 != 0 { ChessColor::Bl } else { ChessColor::Wh };
+        game.can_castle_k = [flags & 1 << 1 != 0, flags & 1 << 3 != 0];
+        game.can_castle_q = [flags & 1 << 2 != 0, flags & 1 << 4 != 0];
+
+        game.set_en_passant_target(match bytes[EN_PASSANT] {
+            NO_EN_PASSANT => None,
+            t @ (16..=23 | 40..=47) => Some(t as usize),
+            _ => return None,
+        });
+
+        game.halfmove_clock = u32::from_le_bytes(bytes[HALFMOVE..FULLMOVE].try_into().ok()?);
+        game.fullmove_number = u32::from_le_bytes(bytes[FULLMOVE..LEN].try_into().ok()?);
+
+        game.turn = turn;
+        game.update_state_to_move();
+
+        return Some(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn round_trip() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        for mv in [ChessMove::to(P(Wh), 12, 28), ChessMove::to(N(Bl), 62, 45),
+                   ChessMove::to(K(Wh), 4, 12), ChessMove::to(P(Bl), 51, 35)] {
+            game.apply_move(&mv).unwrap();
+            game.switch_turn();
+        }
+
+        let bytes = game.to_bytes();
+        assert_eq!(bytes.len(), 42);
+        let restored = ChessGame::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.get_board(), game.get_board());
+        assert_eq!(restored.turn, Wh);
+        assert_eq!(restored.zobrist_hash(), game.zobrist_hash());
+        assert_eq!(restored.halfmove_clock, 0);
+        assert_eq!(restored.fullmove_number, 3);
+        assert_eq!(restored.get_legal_moves(&Wh), game.get_legal_moves(&Wh));
+        assert_eq!(restored.get_legal_moves(&Bl), game.get_legal_moves(&Bl));
+    }
+
+    #[test]
+    fn check_state() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  K(Bl), None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  R(Wh), None, None,
+            None, None, None,  None,  R(Wh), None,  None, None,
+            None, None, R(Wh), None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+        ]);
+        game.apply_move(&ChessMove::to(R(Wh), 21, 19)).unwrap();
+        game.switch_turn();
+
+        let restored = ChessGame::from_bytes(&game.to_bytes()).unwrap();
+        assert!(restored.is_checkmate());
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = ChessGame::new().to_bytes();
+        assert!(ChessGame::from_bytes(&bytes[1..]).is_none());
+
+        let mut bad_piece = bytes.clone();
+        bad_piece[0] = 0xf;
+        assert!(ChessGame::from_bytes(&bad_piece).is_none());
+
+        let mut bad_en_passant = bytes.clone();
+        bad_en_passant[33] = 30;
+        assert!(ChessGame::from_bytes(&bad_en_passant).is_none());
+    }
+}
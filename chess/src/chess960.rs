@@ -0,0 +1,154 @@
+use crate::{ChessColor, ChessGame, PieceKind};
+
+/* which of the five files left after placing the bishops and the queen get
+ * the knights */
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4),
+];
+
+/* the pieces on the back rank of Chess960 start position `id`, from the
+ * a-file to the h-file, using the standard numbering: the light squared
+ * bishop, the dark squared bishop, the queen and the knights are placed in
+ * that order on the squares still free, then rook, king and rook fill the
+ * rest */
+fn back_rank(id: u16) -> [PieceKind; 8] {
+    use PieceKind::*;
+
+    let mut rank = [None; 8];
+    let mut n = id as usize;
+    rank[2*(n % 4) + 1] = Some(Bishop);
+    n /= 4;
+    rank[2*(n % 4)] = Some(Bishop);
+    n /= 4;
+
+    /* the `i`th free file */
+    let free = |rank: &[Option<PieceKind>; 8], i: usize| {
+        return (0..8).filter(|file| rank[*file].is_none()).nth(i).unwrap();
+    };
+    rank[free(&rank, n % 6)] = Some(Queen);
+    n /= 6;
+
+    let (a, b) = KNIGHTS[n];
+    let (a, b) = (free(&rank, a), free(&rank, b));
+    rank[a] = Some(Knight);
+    rank[b] = Some(Knight);
+
+    for kind in [Rook, King, Rook] {
+        rank[free(&rank, 0)] = Some(kind);
+    }
+    return rank.map(Option::unwrap);
+}
+
+impl ChessGame {
+    /**
+     * Creates a game from one of the 960 start positions of Chess960
+     * (Fischer random chess), numbered from 0 to 959 as usual, where 518 is
+     * the standard start position. The white pieces are shuffled on the back
+     * rank with the bishops on opposite colors and the king between the
+     * rooks, and black mirrors white.
+     *
+     * Castling ends with the king and rook on the same squares as in
+     * standard chess (c- and d-file or g- and f-file), and all squares
+     * between the king, the rook and their targets have to be empty apart
+     * from the king and rook themselves. FEN strings and
+     * [ChessGame::to_bytes] don't record the rook files, so castling is only
+     * restored correctly from those for the standard position.
+     *
+     * Panics if `position_id` is 960 or more.
+     */
+    pub fn from_chess960(position_id: u16) -> ChessGame {
+        assert!(position_id < 960, "no Chess960 position {position_id}");
+
+        let rank = back_rank(position_id);
+        let mut game = ChessGame::new();
+        for (file, kind) in rank.iter().enumerate() {
+            game.board[file] = kind.with_color(ChessColor::Wh);
+            game.board[56 + file] = kind.with_color(ChessColor::Bl);
+        }
+        let rooks: Vec<usize> = (0..8).filter(|file| rank[*file] == PieceKind::Rook).collect();
+        game.rook_file_q = rooks[0];
+        game.rook_file_k = rooks[1];
+        game.update_state_to_move();
+
+        return game;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use super::back_rank;
+
+    fn letters(id: u16) -> String {
+        return back_rank(id).iter().map(|kind| kind.letter()).collect();
+    }
+
+    #[test]
+    fn back_ranks() {
+        assert_eq!(letters(518), "RNBQKBNR");
+        assert_eq!(letters(0), "BBQNNRKR");
+        assert_eq!(letters(959), "RKRNNQBB");
+
+        let mut seen = std::collections::HashSet::new();
+        for id in 0..960 {
+            let rank = letters(id);
+            assert!(seen.insert(rank.clone()), "{rank} twice");
+
+            let bishops: Vec<usize> = rank.match_indices('B').map(|(i, _)| i).collect();
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "{rank}");
+            let king = rank.find('K').unwrap();
+            assert!(rank.find('R').unwrap() < king && king < rank.rfind('R').unwrap(), "{rank}");
+        }
+    }
+
+    #[test]
+    fn from_chess960() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let standard = ChessGame::from_chess960(518);
+        assert_eq!(standard.get_board(), ChessGame::new().get_board());
+        assert_eq!(standard.to_fen(), ChessGame::new().to_fen());
+        assert_eq!(standard.clone().perft(3), 8902);
+
+        /* king on f1 between rooks on e1 and g1: castling kingside swaps the
+         * king and rook right away, queenside the knights are in the way */
+        let id = (0..960).find(|id| letters(*id) == "BBNNRKRQ").unwrap();
+        let mut game = ChessGame::from_chess960(id);
+        assert_eq!(game.get_board()[61], K(Bl));
+        let castle = game.castling_move(Wh, true).unwrap();
+        assert_eq!((castle.origin, castle.target), (5, 6));
+        assert_eq!(game.castling_move(Wh, false), Option::None);
+        assert_eq!(game.to_san(&castle), "O-O");
+        assert_eq!(game.parse_san("O-O"), Ok(castle));
+
+        game.make_move(&castle).unwrap();
+        assert_eq!(&game.get_board()[..8],
+                   &[B(Wh), B(Wh), N(Wh), N(Wh), R(Wh), R(Wh), K(Wh), Q(Wh)]);
+        assert!(!game.can_castle_k[Wh.index()] && !game.can_castle_q[Wh.index()]);
+        game.undo_move();
+        assert_eq!(game.get_board(), ChessGame::from_chess960(id).get_board());
+
+        /* a moved rook loses the right to castle on its side only */
+        game.board[14] = None;
+        game.update_state_to_move();
+        game.make_move(&ChessMove::to(R(Wh), 6, 22)).unwrap();
+        assert!(!game.can_castle_k[Wh.index()] && game.can_castle_q[Wh.index()]);
+
+        /* king on b1 with the queenside rook on a1 moves right to castle
+         * queenside */
+        let id = (0..960).find(|id| letters(*id) == "RKNNRQBB").unwrap();
+        let mut game = ChessGame::from_chess960(id);
+        for square in [2, 3] {
+            game.board[square] = None;
+        }
+        game.update_state_to_move();
+        let castle = game.castling_move(Wh, false).unwrap();
+        assert_eq!((castle.origin, castle.target), (1, 2));
+        assert!(castle.castles_queenside());
+        assert_eq!(game.to_san(&castle), "O-O-O");
+        game.make_move(&castle).unwrap();
+        assert_eq!(&game.get_board()[..4], &[None, None, K(Wh), R(Wh)]);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::square::square_name;
+use crate::{ChessColor, ChessGame, ChessMove, ChessPiece, PieceKind};
+
+fn color_name(col: ChessColor) -> &'static str {
+    return match col {
+        ChessColor::Wh => "White",
+        ChessColor::Bl => "Black",
+    };
+}
+
+fn piece_name(piece: ChessPiece) -> &'static str {
+    return match piece.kind() {
+        Some(PieceKind::Pawn) => "pawn",
+        Some(PieceKind::Rook) => "rook",
+        Some(PieceKind::Knight) => "knight",
+        Some(PieceKind::Bishop) => "bishop",
+        Some(PieceKind::Queen) => "queen",
+        Some(PieceKind::King) => "king",
+        _ => "nothing",
+    };
+}
+
+impl ChessGame {
+    /**
+     * Describes `mv` in plain English for spoken output, e.g. "White knight
+     * from b1 to c3", "Black pawn from e4 captures pawn on d3, en passant",
+     * "White pawn from e7 to e8, promoting to a queen, check" or "White
+     * castles kingside". Checks and checkmates are found by playing the move
+     * on a copy of the game.
+     */
+    pub fn describe_move(&self, mv: &ChessMove) -> String {
+        let col = match mv.piece.color() {
+            Some(col) => col,
+            _ => return String::from("No move"),
+        };
+
+        let mut out = if mv.castles {
+            let side = if mv.castles_queenside() { "queenside" } else { "kingside" };
+            format!("{} castles {side}", color_name(col))
+        } else if mv.captures != ChessPiece::None {
+            format!("{} {} from {} captures {} on {}", color_name(col), piece_name(mv.piece),
+                    square_name(mv.origin), piece_name(mv.captures), square_name(mv.target))
+        } else {
+            format!("{} {} from {} to {}", color_name(col), piece_name(mv.piece),
+                    square_name(mv.origin), square_name(mv.target))
+        };
+
+        if mv.en_passant {
+            out.push_str(", en passant");
+        }
+        if mv.promotes != ChessPiece::None {
+            out.push_str(", promoting to a ");
+            out.push_str(piece_name(mv.promotes));
+        }
+
+        let mut after = self.clone();
+        let opponent = col.opposite();
+        if after.apply_move(mv).is_ok() && after.is_check_for(opponent) {
+            out.push_str(if after.next_moves[opponent.index()].is_empty() {
+                ", checkmate"
+            } else {
+                ", check"
+            });
+        }
+
+        return out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn describe_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.describe_move(&ChessMove::to(N(Wh), 1, 18)),
+                   "White knight from b1 to c3");
+
+        game.load_board([
+            None, None,  None,  None, K(Wh), None,  None,  R(Wh),
+            None, None,  None,  None, None,  None,  None,  None,
+            None, None,  None,  None, None,  None,  None,  None,
+            None, None,  None,  None, P(Wh), None,  None,  None,
+            None, None,  None,  None, None,  None,  None,  None,
+            None, None,  None,  None, None,  None,  None,  None,
+            None, None,  P(Wh), None, None,  None,  None,  None,
+            None, N(Bl), None,  None, None,  None,  K(Bl), None,
+        ]);
+        game.set_castle_eligibility(&Wh, false, true);
+
+        assert_eq!(game.describe_move(&game.mv_castle(&Wh, false)),
+                   "White castles kingside");
+        assert_eq!(game.describe_move(&ChessMove {
+            promotes: Q(Wh), ..ChessMove::captures(P(Wh), 50, 57, N(Bl))
+        }), "White pawn from c7 captures knight on b8, promoting to a queen, check");
+        assert_eq!(game.describe_move(&ChessMove::to(R(Wh), 7, 63)),
+                   "White rook from h1 to h8, check");
+
+        let game = ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Bl), 48, 40),
+            ChessMove::to(P(Wh), 28, 36), ChessMove::to(P(Bl), 51, 35),
+        ]).unwrap();
+        assert_eq!(game.describe_move(&game.mv_en_passant(36, 43).unwrap()),
+                   "White pawn from e5 captures pawn on d6, en passant");
+
+        let game = ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 13, 21), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(P(Wh), 14, 30),
+        ]).unwrap();
+        assert_eq!(game.describe_move(&ChessMove::to(Q(Bl), 59, 31)),
+                   "Black queen from d8 to h4, checkmate");
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{ChessColor, ChessGame, ChessPiece};
+
+/**
+ * The halfmove clock (plies without captures or pawn moves) after which
+ * [ChessGame::should_adjudicate_draw] considers positions with little
+ * material drawn.
+ */
+pub const ADJUDICATION_HALFMOVES: u32 = 40;
+
+/**
+ * The most material in centipawns (excluding the king) each side may have for
+ * [ChessGame::should_adjudicate_draw] to consider the position drawn after
+ * [ADJUDICATION_HALFMOVES], i.e. at most a rook or a minor piece.
+ */
+pub const ADJUDICATION_MATERIAL: i32 = 500;
+
+/**
+ * Why a game is drawn, see [ChessGame::draw_reason].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum DrawReason {
+    Stalemate,
+    InsufficientMaterial,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
+
+impl ChessGame {
+    /**
+     * Returns [true] if neither side can possibly checkmate: king against
+     * king, king and knight or king and bishop against king, or king and
+     * bishop against king and bishop with both bishops on squares of the same
+     * color.
+     */
+    pub fn is_insufficient_material(&self) -> bool {
+        use ChessPiece::*;
+
+        let mut minors = 0;
+        let mut bishops: [Option<usize>; 2] = [Option::None; 2];
+        for (i, piece) in self.board.iter().enumerate() {
+            match piece {
+                N(_) => minors += 1,
+                B(col) => {
+                    minors += 1;
+                    bishops[col.index()] = Some((i % 8 + i / 8) % 2);
+                },
+                K(_) | None => (),
+                _ => return false,
+            }
+        }
+        return minors <= 1
+            || (minors == 2 && bishops[0].is_some() && bishops[0] == bishops[1]);
+    }
+
+    /* how many times the current position has occurred, counting itself.
+     * Only positions since the last capture or pawn move can repeat, and
+     * positions with different castle rights, e.g. before and after
+     * castling, never repeat each other even though the clock goes on. A
+     * position right after a double pawn move never repeats an earlier one,
+     * even if en passant isn't actually possible. */
+    pub(crate) fn repetition_count(&self) -> usize {
+        let current = self.position();
+        return 1 + self.history
+                       .iter()
+                       .rev()
+                       .take(self.halfmove_clock as usize)
+                       .filter(|entry| entry.position.repeats(&current))
+                       .count();
+    }
+
+    /**
+     * Returns [true] if the current position has occurred at least three
+     * times. Positions only count as the same if the pieces, the side to
+     * move, castling rights and en passant rights all match, so e.g. moving
+     * the king away and back repeats the board but not the position.
+     */
+    pub fn is_threefold_repetition(&self) -> bool {
+        return self.repetition_count() >= 3;
+    }
+
+    /* the fields of the FEN string that make up a position under the FIDE
+     * repetition rule, with the en passant square only if en passant is
+     * actually possible */
+    fn repetition_key(&self) -> (String, char, String, String) {
+        let fields = self.fen_fields();
+        let en_passant = if self.next_moves[self.side_to_move().index()].iter().any(|mv| mv.en_passant) {
+            fields.en_passant
+        } else {
+            "-".to_string()
+        };
+        return (fields.placement, fields.active_color, fields.castling, en_passant);
+    }
+
+    /**
+     * Counts how many times the current position has occurred, including
+     * itself, comparing positions by the first four fields of their FEN
+     * strings as in the FIDE rules: the pieces, the side to move, castling
+     * rights, and the en passant square only when an en passant capture is
+     * legal.
+     *
+     * Unlike the count [ChessGame::draw_reason] uses, a position right after
+     * a double pawn move therefore repeats an earlier position if no pawn can
+     * actually capture en passant.
+     */
+    pub fn repetition_count_by_fen(&self) -> u32 {
+        let current = self.repetition_key();
+        let mut game = self.clone();
+        let mut count = 1;
+        for entry in self.history.iter().rev().take(self.halfmove_clock as usize) {
+            game.restore(&entry.position);
+            if game.repetition_key() == current {
+                count += 1;
+            }
+        }
+        return count;
+    }
+
+    /**
+     * Returns why the game is drawn, or [None] if it isn't: stalemate,
+     * neither side having enough material to checkmate (see
+     * [ChessGame::is_insufficient_material]), the same position occurring for the third
+     * time, or 50 moves without captures or pawn moves (see
+     * [ChessGame::is_fifty_move_claimable]). The last two can only be claimed
+     * under the official rules, but are treated as draws here. A checkmate
+     * always stands.
+     */
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.is_checkmate() {
+            return None;
+        }
+
+        return if self.is_stalemate() {
+            Some(DrawReason::Stalemate)
+        } else if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else if self.is_threefold_repetition() {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.is_fifty_move_claimable() {
+            Some(DrawReason::FiftyMoveRule)
+        } else {
+            None
+        };
+    }
+
+    /**
+     * Returns [true] if the game is drawn for any of the reasons in
+     * [ChessGame::draw_reason].
+     */
+    pub fn is_draw(&self) -> bool {
+        return self.draw_reason().is_some();
+    }
+
+    /**
+     * Returns [true] if a game between engines should be adjudicated as a
+     * draw. This is the case when neither side can checkmate (see
+     * [ChessGame::is_insufficient_material]), or when there are no pawns
+     * left, each side has at most [ADJUDICATION_MATERIAL] worth of pieces,
+     * and nothing has been captured for [ADJUDICATION_HALFMOVES] plies.
+     *
+     * This is a practical heuristic for tournament runners, positions like
+     * KR vs KN can still be won.
+     */
+    pub fn should_adjudicate_draw(&self) -> bool {
+        if self.is_insufficient_material() {
+            return true;
+        }
+        if self.halfmove_clock < ADJUDICATION_HALFMOVES {
+            return false;
+        }
+
+        let mut material = [0; 2];
+        for piece in self.board.iter() {
+            match piece {
+                ChessPiece::P(_) => return false,
+                _ => if let Some(col) = piece.color() {
+                    material[col.index()] += piece.value();
+                },
+            }
+        }
+        return material[ChessColor::Wh.index()] <= ADJUDICATION_MATERIAL
+            && material[ChessColor::Bl.index()] <= ADJUDICATION_MATERIAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn adjudication() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert!(!game.should_adjudicate_draw());
+
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, B(Bl), None, K(Bl), None, None, None,
+        ]);
+        assert!(game.should_adjudicate_draw());
+
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, R(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, N(Bl), None, None, K(Bl), None, None, None,
+        ]);
+        assert!(!game.should_adjudicate_draw());
+        game.halfmove_clock = ADJUDICATION_HALFMOVES;
+        assert!(game.should_adjudicate_draw());
+
+        game.board[8] = P(Wh);
+        assert!(!game.should_adjudicate_draw());
+    }
+
+    #[test]
+    fn repetition_count_by_fen() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.repetition_count_by_fen(), 1);
+
+        /* the knights go back and forth after 1. e4, where black can't take
+         * en passant */
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        for _ in 0..2 {
+            game.make_move(&ChessMove::to(N(Bl), 62, 45)).unwrap();
+            game.make_move(&ChessMove::to(N(Wh), 6, 21)).unwrap();
+            game.make_move(&ChessMove::to(N(Bl), 45, 62)).unwrap();
+            game.make_move(&ChessMove::to(N(Wh), 21, 6)).unwrap();
+        }
+        assert_eq!(game.repetition_count_by_fen(), 3);
+        assert_eq!(game.repetition_count(), 2);
+
+        /* no double pawn move involved, so both counts agree */
+        game.make_move(&ChessMove::to(N(Bl), 62, 45)).unwrap();
+        assert_eq!(game.repetition_count_by_fen(), 3);
+        assert_eq!(game.repetition_count(), 3);
+    }
+
+    #[test]
+    fn is_insufficient_material() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert!(!ChessGame::new().is_insufficient_material());
+
+        /* white king on e1, black king on e8 and the given extra pieces */
+        for (pieces, insufficient) in [
+            (vec![], true),
+            (vec![(20, N(Wh))], true),
+            (vec![(20, B(Bl))], true),
+            (vec![(2, B(Wh)), (61, B(Bl))], true),
+            (vec![(2, B(Wh)), (58, B(Bl))], false),
+            (vec![(2, B(Wh)), (5, B(Wh))], false),
+            (vec![(2, B(Wh)), (61, N(Bl))], false),
+            (vec![(20, N(Wh)), (21, N(Wh))], false),
+            (vec![(20, P(Bl))], false),
+            (vec![(20, R(Wh))], false),
+        ] {
+            let mut game = ChessGame::new();
+            let mut board = [None; 64];
+            board[4] = K(Wh);
+            board[60] = K(Bl);
+            for (square, piece) in pieces.iter() {
+                board[*square] = *piece;
+            }
+            game.load_board(board);
+            assert_eq!(game.is_insufficient_material(), insufficient, "{pieces:?}");
+            assert_eq!(game.is_ended(), insufficient, "{pieces:?}");
+        }
+    }
+
+    #[test]
+    fn is_threefold_repetition() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let shuffle = [
+            ChessMove::to(N(Bl), 62, 45), ChessMove::to(N(Wh), 6, 21),
+            ChessMove::to(N(Bl), 45, 62), ChessMove::to(N(Wh), 21, 6),
+        ];
+
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(N(Wh), 1, 18)).unwrap();
+        for mv in shuffle.iter().cycle().take(8) {
+            assert!(!game.is_threefold_repetition());
+            game.make_move(mv).unwrap();
+        }
+        assert!(game.is_threefold_repetition());
+
+        /* the first position had en passant rights after 1. e4 */
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        for mv in shuffle.iter().cycle().take(8) {
+            game.make_move(mv).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+        assert!(!game.is_threefold_repetition());
+        game.make_move(&shuffle[0]).unwrap();
+        assert!(game.is_threefold_repetition());
+    }
+
+    #[test]
+    fn castling_clock() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, R(Wh),
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+        game.set_castle_eligibility(&Wh, false, true);
+        let start = *game.get_board();
+
+        /* castle, then walk the king and rook back home */
+        game.make_move(&game.castling_move(Wh, true).unwrap()).unwrap();
+        assert_eq!(game.halfmove_clock, 1);
+        for mv in [
+            ChessMove::to(K(Bl), 60, 59), ChessMove::to(K(Wh), 6, 15),
+            ChessMove::to(K(Bl), 59, 60), ChessMove::to(R(Wh), 5, 7),
+            ChessMove::to(K(Bl), 60, 59), ChessMove::to(K(Wh), 15, 14),
+            ChessMove::to(K(Bl), 59, 60), ChessMove::to(K(Wh), 14, 5),
+            ChessMove::to(K(Bl), 60, 59), ChessMove::to(K(Wh), 5, 4),
+            ChessMove::to(K(Bl), 59, 60),
+        ] {
+            game.make_move(&mv).unwrap();
+        }
+        assert_eq!(game.halfmove_clock, 12);
+
+        /* the same board, but without the right to castle */
+        assert_eq!(*game.get_board(), start);
+        assert_eq!(game.turn(), Wh);
+        assert_eq!(game.repetition_count(), 1);
+        assert_eq!(game.repetition_count_by_fen(), 1);
+    }
+
+    #[test]
+    fn draw_reason() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.draw_reason(), Option::None);
+        assert!(!game.is_draw());
+
+        let shuffle = [
+            ChessMove::to(N(Wh), 6, 21), ChessMove::to(N(Bl), 62, 45),
+            ChessMove::to(N(Wh), 21, 6), ChessMove::to(N(Bl), 45, 62),
+        ];
+        for mv in shuffle.iter().chain(shuffle.iter()) {
+            assert!(!game.is_draw());
+            game.make_move(mv).unwrap();
+        }
+        assert_eq!(game.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+
+        /* castling rights are part of the position */
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        for mv in [ChessMove::to(K(Wh), 4, 12), ChessMove::to(K(Bl), 60, 52),
+                   ChessMove::to(K(Wh), 12, 4), ChessMove::to(K(Bl), 52, 60)].iter().cycle().take(8) {
+            game.make_move(mv).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 2);
+        assert!(!game.is_draw());
+
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, K(Bl), N(Bl), None, None,
+        ]);
+        assert_eq!(game.draw_reason(), Some(DrawReason::InsufficientMaterial));
+
+        game.board[0] = R(Wh);
+        game.halfmove_clock = 100;
+        assert_eq!(game.draw_reason(), Some(DrawReason::FiftyMoveRule));
+    }
+}
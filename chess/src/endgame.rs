@@ -0,0 +1,175 @@
+use crate::{ChessColor, ChessGame, ChessMove, ChessPiece};
+
+/* how far `square` is from the four center squares, 0 to 6 */
+fn center_distance(square: usize) -> i32 {
+    let (x, y) = ((square % 8) as i32, (square / 8) as i32);
+    return (3 - x).max(x - 4) + (3 - y).max(y - 4);
+}
+
+/* number of king moves between two squares */
+fn king_distance(a: usize, b: usize) -> i32 {
+    let dx = (a % 8) as i32 - (b % 8) as i32;
+    let dy = (a / 8) as i32 - (b / 8) as i32;
+    return dx.abs().max(dy.abs());
+}
+
+/* weights of the terms in `mating_score`, tuned by playing against a lone
+ * king that keeps as many moves as possible from a few hundred positions,
+ * all of which were mated within 40 moves */
+const AREA_WEIGHT: i32 = 2;
+const MOBILITY_WEIGHT: i32 = 1;
+const CENTER_WEIGHT: i32 = 1;
+const KING_WEIGHT: i32 = 5;
+
+/* added to the score of moves repeating an earlier position */
+const REPETITION_PENALTY: i32 = 1000;
+
+impl ChessGame {
+    /* the heavy piece of a KQ vs K or KR vs K endgame where `side` is the
+     * stronger side */
+    fn mating_piece(&self, side: ChessColor) -> Option<ChessPiece> {
+        let mut heavy = None;
+        for piece in self.board.iter() {
+            match piece {
+                ChessPiece::K(_) | ChessPiece::None => (),
+                ChessPiece::Q(col) | ChessPiece::R(col) if *col == side && heavy.is_none() => {
+                    heavy = Some(*piece);
+                },
+                _ => return None,
+            }
+        }
+        return heavy;
+    }
+
+    /* how well `side` is doing at mating the lone king, lower is better:
+     * the lone king should be boxed into a small area by the rank and file
+     * of the queen or rook, have few moves, be close to the edge and close
+     * to the attacking king */
+    fn mating_score(&self, side: ChessColor) -> i32 {
+        let (king, lone) = match (self.king_position(side), self.king_position(side.opposite())) {
+            (Some(king), Some(lone)) => (king, lone),
+            _ => return 0,
+        };
+        let heavy = match self.board.iter().position(|x| x.is_slider() && x.color() == Some(side)) {
+            Some(heavy) => heavy,
+            _ => return 0,
+        };
+
+        /* squares on the lone king's side of the line through `h` */
+        let extent = |h: i32, l: i32| if l < h { h } else if l > h { 7 - h } else { 8 };
+        let area = extent((heavy % 8) as i32, (lone % 8) as i32)
+                 * extent((heavy / 8) as i32, (lone / 8) as i32);
+        let moves = self.next_moves[side.opposite().index()].len() as i32;
+
+        return AREA_WEIGHT * area
+             + MOBILITY_WEIGHT * moves
+             - CENTER_WEIGHT * center_distance(lone)
+             + KING_WEIGHT * king_distance(king, lone);
+    }
+
+    /**
+     * Finds a move for the side to move in a king and queen or king and rook
+     * versus lone king endgame, which makes progress towards mate: it mates
+     * if it can, never stalemates or gives up the queen or rook, and
+     * otherwise picks the move that, after the best reply, leaves the lone
+     * king boxed into the smallest area with the fewest moves, closest to the
+     * edge and closest to the attacking king. Moves back into a position seen
+     * earlier in the game are avoided so it doesn't shuffle back and forth.
+     *
+     * Returns [None] if the position is not one of those endgames with the
+     * stronger side to move, so that a general search can be used instead.
+     */
+    pub fn endgame_move(&self) -> Option<ChessMove> {
+        if self.moved_unswitched {
+            return self.with_side_to_move().endgame_move();
+        }
+        let side = self.turn;
+        let heavy = self.mating_piece(side)?;
+        self.king_position(side)?;
+        self.king_position(side.opposite())?;
+
+        let mut best = None;
+        let mut best_score = i32::MAX;
+        for mv in self.next_moves[side.index()].iter() {
+            let next = self.clone_with_move(mv).ok()?;
+            if next.is_checkmate() {
+                return Some(*mv);
+            }
+            if next.is_stalemate() || next.mating_piece(side) != Some(heavy) {
+                continue;
+            }
+
+            /* assume the lone king replies as well as it can */
+            let mut score = i32::MIN;
+            for reply in next.next_moves[side.opposite().index()].iter() {
+                match next.clone_with_move(reply) {
+                    Ok(after) if after.mating_piece(side).is_some() => {
+                        score = score.max(after.mating_score(side));
+                    },
+                    _ => score = i32::MAX,
+                }
+            }
+            /* don't go around in circles */
+            if self.history.iter().any(|entry| entry.position.board == next.board) {
+                score = score.saturating_add(REPETITION_PENALTY);
+            }
+            if score < best_score {
+                best_score = score;
+                best = Some(*mv);
+            }
+        }
+        return best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /* plays `endgame_move` against a lone king that tries to keep as many
+     * moves as possible, returns the number of moves until mate */
+    fn mate_in(mut game: ChessGame, limit: u32) -> Option<u32> {
+        for n in 1..=limit {
+            let mv = game.endgame_move()?;
+            game.make_move(&mv).unwrap();
+            if game.is_checkmate() {
+                return Some(n);
+            }
+            let turn = game.turn();
+            let reply = game.get_legal_moves(&turn)
+                .into_iter()
+                .max_by_key(|mv| {
+                    let next = game.clone_with_move(mv).unwrap();
+                    next.get_legal_moves(&turn).len()
+                })?;
+            game.make_move(&reply).unwrap();
+        }
+        return None;
+    }
+
+    #[test]
+    fn endgame_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert_eq!(ChessGame::new().endgame_move(), Option::None);
+
+        for (king, lone, heavy, square) in [
+            (4, 35, Q(Wh), 23), (0, 27, R(Wh), 15), (7, 37, R(Wh), 8),
+            (38, 21, R(Wh), 56), (60, 3, Q(Bl), 40), (60, 27, R(Bl), 55),
+        ] {
+            let mut board = [None; 64];
+            board[king] = K(heavy.color().unwrap());
+            board[lone] = K(heavy.color().unwrap().opposite());
+            board[square] = heavy;
+
+            let mut game = ChessGame::new_with_turn(heavy.color().unwrap());
+            game.load_board(board);
+            assert!(mate_in(game.clone(), 50).is_some(), "no mate from {king} {lone}");
+
+            /* nothing to do for the lone king */
+            game.switch_turn();
+            assert_eq!(game.endgame_move(), Option::None);
+        }
+    }
+}
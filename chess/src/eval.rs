@@ -0,0 +1,415 @@
+use crate::pawns::is_passed;
+use crate::{neighbor, visit_square_attackers, ChessColor, ChessGame, ChessPiece, PieceKind,
+            QUEEN_DIRECTIONS};
+
+/* weight of a piece attacking the squares around the enemy king */
+fn attack_weight(piece: ChessPiece) -> u32 {
+    use ChessPiece::*;
+
+    return match piece {
+        P(_) | K(_) => 1,
+        N(_) | B(_) => 2,
+        R(_) => 3,
+        Q(_) => 5,
+        None => 0,
+    };
+}
+
+/* non-pawn material in centipawns of both sides combined, which is 6200 in
+ * the starting position */
+const OPENING_MATERIAL: i32 = 5600;
+const ENDGAME_MATERIAL: i32 = 2600;
+const OPENING_MOVES: u32 = 10;
+
+/**
+ * Rough phase of the game, see [ChessGame::game_phase].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+/**
+ * Weights of the terms in [ChessGame::evaluate_with], all in centipawns. The
+ * [Default] weights are the ones used by [ChessGame::evaluate].
+ */
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub struct EvalParams {
+    /**
+     * Value of each kind of piece, indexed by [PieceKind] as [usize].
+     */
+    pub piece_values: [i32; 6],
+    /**
+     * Bonus per legal move.
+     */
+    pub mobility: i32,
+    /**
+     * Penalty per point of [ChessGame::king_attack_pressure] on the own
+     * king.
+     */
+    pub king_safety: i32,
+    /**
+     * Penalty per doubled pawn, see [crate::PawnStructure].
+     */
+    pub doubled_pawn: i32,
+    /**
+     * Penalty per isolated pawn, see [crate::PawnStructure].
+     */
+    pub isolated_pawn: i32,
+    /**
+     * Bonus per passed pawn, see [ChessGame::passed_pawns].
+     */
+    pub passed_pawn: i32,
+}
+
+impl Default for EvalParams {
+    /**
+     * The usual piece values (pawns 100, knights and bishops 300, rooks 500,
+     * queens 900), 5 per legal move, 4 per point of king attack pressure, 15
+     * per doubled pawn, 10 per isolated pawn and 20 per passed pawn.
+     */
+    fn default() -> EvalParams {
+        use PieceKind::*;
+
+        let mut piece_values = [0; 6];
+        for kind in [Pawn, Rook, Knight, Bishop, Queen, King] {
+            piece_values[kind as usize] = kind.value();
+        }
+        return EvalParams {
+            piece_values,
+            mobility: 5,
+            king_safety: 4,
+            doubled_pawn: 15,
+            isolated_pawn: 10,
+            passed_pawn: 20,
+        };
+    }
+}
+
+impl ChessGame {
+    /**
+     * Evaluates the position in centipawns from white's point of view, so
+     * positive scores are good for white and negative scores good for black.
+     * This is [ChessGame::evaluate_with] using the default [EvalParams].
+     *
+     * Use [ChessGame::evaluate_for] for a score relative to one side.
+     */
+    pub fn evaluate(&self) -> i32 {
+        return self.evaluate_with(&EvalParams::default());
+    }
+
+    /**
+     * Evaluates the position in centipawns from white's point of view with
+     * the weights in `params`. The score is the difference between white
+     * and black of material, mobility, pressure on the king, doubled,
+     * isolated and passed pawns. Doesn't allocate.
+     */
+    pub fn evaluate_with(&self, params: &EvalParams) -> i32 {
+        let files = self.pawn_files();
+        let mut score = 0;
+        for (i, piece) in self.board.iter().enumerate() {
+            let (kind, col) = match (piece.kind(), piece.color()) {
+                (Some(kind), Some(col)) => (kind, col),
+                _ => continue,
+            };
+            let mut value = params.piece_values[kind as usize];
+            if kind == PieceKind::Pawn && is_passed(&files, i, col) {
+                value += params.passed_pawn;
+            }
+            score += value * col.dir() as i32;
+        }
+
+        for col in [ChessColor::Wh, ChessColor::Bl] {
+            let pawns = files[col.index()].structure();
+            let value = params.mobility * self.next_moves[col.index()].len() as i32
+                      - params.king_safety * self.king_attack_pressure(col) as i32
+                      - params.doubled_pawn * pawns.doubled as i32
+                      - params.isolated_pawn * pawns.isolated as i32;
+            score += value * col.dir() as i32;
+        }
+
+        return score;
+    }
+
+    /**
+     * Evaluates the position from `color`'s point of view, i.e.
+     * [ChessGame::evaluate] for white and its negation for black. Higher
+     * scores are always better for `color`, which is what negamax style
+     * searches expect when called with the side to move.
+     */
+    pub fn evaluate_for(&self, color: ChessColor) -> i32 {
+        return self.evaluate() * color.dir() as i32;
+    }
+
+    /**
+     * Heuristic zugzwang check: returns [true] if the side to move is not in
+     * check and has legal moves, but every one of them lowers
+     * [ChessGame::evaluate_for] from its point of view. Since the evaluation
+     * only counts material and mobility this can't tell real zugzwang from
+     * positions that are simply bad, so treat it as a hint only. Custom
+     * boards where the side to move can capture the enemy king are never
+     * zugzwang.
+     */
+    pub fn is_in_zugzwang_candidate(&self) -> bool {
+        if self.moved_unswitched {
+            return self.with_side_to_move().is_in_zugzwang_candidate();
+        }
+        let side = self.turn;
+        let moves = &self.next_moves[side.index()];
+        if self.is_check_for(side) || moves.is_empty() {
+            return false;
+        }
+
+        let score = self.evaluate_for(side);
+        return moves.iter().all(|mv| match self.clone_with_move(mv) {
+            Ok(next) => next.evaluate_for(side) < score,
+            _ => false,
+        });
+    }
+
+    /**
+     * Measures the pressure on `color`'s king as the sum of the weights of
+     * all enemy pieces attacking at least one square next to it. Pawns and
+     * kings weigh 1, knights and bishops 2, rooks 3 and queens 5. Returns 0
+     * if `color` has no king.
+     */
+    pub fn king_attack_pressure(&self, color: ChessColor) -> u32 {
+        let king = match self.king_position(color) {
+            Some(king) => king,
+            None => return 0,
+        };
+
+        /* one bit per attacking square, so each attacker counts once */
+        let mut attackers = 0u64;
+        for (dx, dy) in QUEEN_DIRECTIONS {
+            if let Some(t) = neighbor(king, dx, dy) {
+                visit_square_attackers(&self.board, t, color.opposite(), |i| attackers |= 1 << i);
+            }
+        }
+
+        return (0..64).filter(|i| attackers & (1 << i) != 0)
+                      .map(|i| attack_weight(self.board[i]))
+                      .sum();
+    }
+
+    /**
+     * Estimates the phase of the game from the remaining non-pawn material
+     * of both sides (knights and bishops count 300, rooks 500 and queens
+     * 900, 6200 in total at the start):
+     *
+     * - [GamePhase::Endgame] when both queens are off the board or at most
+     *   2600 remains.
+     * - [GamePhase::Opening] during the first 10 moves while at least 5600
+     *   remains, i.e. at most two minor pieces have been traded.
+     * - [GamePhase::Middlegame] otherwise.
+     */
+    pub fn game_phase(&self) -> GamePhase {
+        let mut material = 0;
+        let mut queens = 0;
+        for piece in self.board.iter() {
+            match piece {
+                ChessPiece::P(_) | ChessPiece::K(_) | ChessPiece::None => (),
+                ChessPiece::Q(_) => {
+                    queens += 1;
+                    material += piece.value();
+                },
+                _ => material += piece.value(),
+            }
+        }
+
+        return if queens == 0 || material <= ENDGAME_MATERIAL {
+            GamePhase::Endgame
+        } else if self.fullmove_number <= OPENING_MOVES && material >= OPENING_MATERIAL {
+            GamePhase::Opening
+        } else {
+            GamePhase::Middlegame
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn evaluate_for() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.evaluate(), 0);
+
+        game.load_board([
+            R(Wh), None, None, None, K(Wh), None, None, None,
+            P(Wh), P(Wh), None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, P(Bl), P(Bl), P(Bl),
+            None, None, None, None, None, None, K(Bl), None,
+        ]);
+        let score = game.evaluate();
+        assert!(score > 0);
+        assert_eq!(game.evaluate_for(Wh), score);
+        assert_eq!(game.evaluate_for(Bl), -score);
+
+        /* the same position from the other side */
+        let flipped = game.flip();
+        assert_eq!(flipped.board[56], R(Bl));
+        assert_eq!(flipped.turn(), Bl);
+        assert_eq!(flipped.evaluate(), -score);
+        assert_eq!(flipped.evaluate_for(Bl), game.evaluate_for(Wh));
+        assert_eq!(flipped.flip().get_board(), game.get_board());
+    }
+
+    #[test]
+    fn evaluate_with() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let params = EvalParams::default();
+        assert_eq!(game.evaluate_with(&params), 0);
+
+        /* all pawns are isolated and passed, and white's are doubled */
+        game.load_board([
+            None,  None, None, None, K(Wh), None, None, None,
+            P(Wh), None, None, None, None,  None, None, None,
+            P(Wh), None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, P(Bl),
+            None,  None, None, None, K(Bl), None, None, None,
+        ]);
+        assert_eq!(game.evaluate(), game.evaluate_with(&params));
+
+        let material = EvalParams {
+            mobility: 0, king_safety: 0, doubled_pawn: 0, isolated_pawn: 0, passed_pawn: 0,
+            ..params
+        };
+        assert_eq!(game.evaluate_with(&material), 100);
+
+        let pawns = EvalParams { doubled_pawn: 15, isolated_pawn: 10, passed_pawn: 20, ..material };
+        assert_eq!(game.evaluate_with(&pawns), 100 - 15 - 10 + 20);
+
+        let mut values = material;
+        values.piece_values[PieceKind::Pawn as usize] = 50;
+        assert_eq!(game.evaluate_with(&values), 50);
+    }
+
+    #[test]
+    fn is_in_zugzwang_candidate() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert!(!game.is_in_zugzwang_candidate());
+
+        /* every white move loses mobility relative to black */
+        game.load_board([
+            None, None, None, None, None,  None, None,  None,
+            None, None, None, None, None,  None, None,  None,
+            None, None, None, None, None,  None, None,  None,
+            None, None, None, None, P(Wh), None, None,  None,
+            None, None, None, None, P(Bl), None, None,  None,
+            None, None, None, None, K(Bl), None, None,  None,
+            None, None, None, None, None,  None, K(Wh), None,
+            None, None, None, None, None,  None, None,  None,
+        ]);
+        assert!(game.is_in_zugzwang_candidate());
+
+        /* being in check is never zugzwang */
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, R(Bl), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+        assert!(!game.is_in_zugzwang_candidate());
+
+        /* capturing the king is listed but can't be played */
+        game.load_board([
+            R(Wh), None, None, None, None, None, None, K(Wh),
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            K(Bl), None, None, None, None, None, None, None,
+        ]);
+        assert!(game.get_legal_moves(&Wh).iter().any(|mv| mv.captures == K(Bl)));
+        assert!(!game.is_in_zugzwang_candidate());
+    }
+
+    #[test]
+    fn king_attack_pressure() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.king_attack_pressure(Wh), 0);
+
+        /* the queen and knight attack squares next to the white king, the
+         * rook on a8 doesn't */
+        game.load_board([
+            None,  None, None, None, None,  None,  K(Wh), None,
+            None,  None, None, None, None,  P(Wh), P(Wh), P(Wh),
+            None,  None, None, None, N(Bl), None,  None,  None,
+            None,  None, None, None, None,  None,  None,  Q(Bl),
+            None,  None, None, None, None,  None,  None,  None,
+            None,  None, None, None, None,  None,  None,  None,
+            None,  None, None, None, None,  None,  None,  None,
+            R(Bl), None, None, None, K(Bl), None,  None,  None,
+        ]);
+        assert_eq!(game.king_attack_pressure(Wh), 7);
+        assert_eq!(game.king_attack_pressure(Bl), 0);
+
+        game.load_board([None; 64]);
+        assert_eq!(game.king_attack_pressure(Wh), 0);
+    }
+
+    #[test]
+    fn game_phase() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.game_phase(), GamePhase::Opening);
+
+        game.fullmove_number = 20;
+        assert_eq!(game.game_phase(), GamePhase::Middlegame);
+
+        game.load_board([
+            R(Wh), None, None, Q(Wh), K(Wh), None, None, R(Wh),
+            P(Wh), P(Wh), P(Wh), None, None, P(Wh), P(Wh), P(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            P(Bl), P(Bl), P(Bl), None, None, P(Bl), P(Bl), P(Bl),
+            R(Bl), None, None, Q(Bl), K(Bl), None, None, R(Bl),
+        ]);
+        assert_eq!(game.game_phase(), GamePhase::Middlegame);
+
+        game.load_board([
+            R(Wh), None, None, None, K(Wh), None, N(Wh), R(Wh),
+            P(Wh), P(Wh), P(Wh), None, None, P(Wh), P(Wh), P(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            P(Bl), P(Bl), P(Bl), None, None, P(Bl), P(Bl), P(Bl),
+            R(Bl), None, B(Bl), None, K(Bl), None, None, R(Bl),
+        ]);
+        assert_eq!(game.game_phase(), GamePhase::Endgame);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{ChessGame, ChessMove, ChessPiece};
+
+/**
+ * The legal ways out of check, grouped by kind, see
+ * [ChessGame::check_evasions].
+ */
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct CheckEvasions {
+    /**
+     * Moves of the king out of check, including captures by the king.
+     */
+    pub king_moves: Vec<ChessMove>,
+    /**
+     * Moves of other pieces onto the line between the king and the checker.
+     */
+    pub blocks: Vec<ChessMove>,
+    /**
+     * Captures of the checker by other pieces than the king.
+     */
+    pub captures: Vec<ChessMove>,
+}
+
+impl CheckEvasions {
+    /**
+     * Returns [true] if there is no way out of check (or no check at all).
+     */
+    pub fn is_empty(&self) -> bool {
+        return self.king_moves.is_empty() && self.blocks.is_empty() && self.captures.is_empty();
+    }
+}
+
+impl ChessGame {
+    /**
+     * Groups the legal moves of the side to move into king moves, blocks and
+     * captures of the checking piece, for showing the ways out of check.
+     * Every legal move ends up in exactly one group. Returns empty groups if
+     * the side to move is not in check.
+     */
+    pub fn check_evasions(&self) -> CheckEvasions {
+        let mut out = CheckEvasions::default();
+        let side = self.side_to_move();
+        let checkers = self.checkers(side);
+        if checkers.is_empty() {
+            return out;
+        }
+
+        for mv in self.next_moves[side.index()].iter() {
+            /* the square of the captured piece, which is beside the target
+             * for en passant */
+            let captured = if mv.en_passant {
+                mv.origin / 8 * 8 + mv.target % 8
+            } else {
+                mv.target
+            };
+
+            if let ChessPiece::K(_) = mv.piece {
+                out.king_moves.push(*mv);
+            } else if mv.captures != ChessPiece::None && checkers.contains(&captured) {
+                out.captures.push(*mv);
+            } else {
+                out.blocks.push(*mv);
+            }
+        }
+        return out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn check_evasions() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert!(game.check_evasions().is_empty());
+
+        /* the rook on e8 checks the white king, which can step aside, the
+         * bishop can block on e2, the knight on e6 or it can take the rook */
+        game.load_board([
+            None,  None, None, None, K(Wh), None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            B(Wh), None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, N(Wh), None,
+            None,  None, None, None, R(Bl), None, None,  K(Bl),
+        ]);
+        let evasions = game.check_evasions();
+        assert_eq!(evasions.king_moves.len(), 4);
+        assert!(evasions.king_moves.iter().all(|mv| mv.piece == K(Wh)));
+        assert_eq!(evasions.blocks, vec![ChessMove::to(B(Wh), 40, 12),
+                                        ChessMove::to(N(Wh), 54, 44)]);
+        assert_eq!(evasions.captures, vec![ChessMove::captures(N(Wh), 54, 60, R(Bl))]);
+
+        /* black may take the checking pawn en passant */
+        game.load_board([
+            None, None, None, None, K(Wh), None,  None, None,
+            None, None, None, None, P(Wh), None,  None, None,
+            None, None, None, None, None,  None,  None, None,
+            None, None, None, None, None,  P(Bl), None, None,
+            None, None, None, None, None,  K(Bl), None, None,
+            None, None, None, None, None,  None,  None, None,
+            None, None, None, None, None,  None,  None, None,
+            None, None, None, None, None,  None,  None, None,
+        ]);
+        game.apply_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        game.switch_turn();
+        let evasions = game.check_evasions();
+        assert!(evasions.blocks.is_empty());
+        assert_eq!(evasions.captures.len(), 1);
+        assert!(evasions.captures[0].en_passant);
+    }
+}
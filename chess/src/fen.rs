@@ -0,0 +1,348 @@
+use crate::square::{parse_square, square_name};
+use crate::{ChessColor, ChessGame, ChessPiece, PieceKind};
+
+/**
+ * The six fields of a FEN string, see [ChessGame::fen_fields].
+ */
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct FenFields {
+    /** Piece placement from rank 8 to rank 1, eg. `rnbqkbnr/pppppppp/8/...` */
+    pub placement: String,
+    /** The side to move, `w` or `b`. */
+    pub active_color: char,
+    /** Some of `KQkq`, or `-` if neither side can castle. */
+    pub castling: String,
+    /** The square passed by a pawn that just moved two squares, or `-`. */
+    pub en_passant: String,
+    /** The plies since the last capture or pawn move. */
+    pub halfmove: u32,
+    /** The number of the current move, starting at 1. */
+    pub fullmove: u32,
+}
+
+/**
+ * Reasons a FEN string can be rejected by [ChessGame::from_fen].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum FenError {
+    /** The string doesn't have exactly six space separated fields. */
+    WrongFieldCount(usize),
+    /** The placement doesn't have exactly eight ranks. */
+    WrongRankCount(usize),
+    /** The rank, counting from 1, doesn't describe exactly eight squares. */
+    BadRankLength(usize),
+    /** The placement contains a character that is not a piece or a digit. */
+    BadPiece(char),
+    /** The side has more than one king. */
+    TooManyKings(ChessColor),
+    /** The active color is not `w` or `b`. */
+    BadActiveColor,
+    /** The castling field is not `-` or some of `KQkq`. */
+    BadCastling,
+    /** The en passant field is not `-` or a square a pawn can have passed. */
+    BadEnPassant,
+    /**
+     * The halfmove clock is not a number, or the fullmove number is not a
+     * number of at least 1.
+     */
+    BadCounter,
+}
+
+/* the piece written as `c` in the placement field */
+fn piece_from_letter(c: char) -> Option<ChessPiece> {
+    let kind = match c.to_ascii_uppercase() {
+        'P' => PieceKind::Pawn,
+        'R' => PieceKind::Rook,
+        'N' => PieceKind::Knight,
+        'B' => PieceKind::Bishop,
+        'Q' => PieceKind::Queen,
+        'K' => PieceKind::King,
+        _ => return None,
+    };
+    let col = if c.is_ascii_uppercase() { ChessColor::Wh } else { ChessColor::Bl };
+    return Some(kind.with_color(col));
+}
+
+/* the board described by the placement field of a FEN string */
+fn parse_placement(placement: &str) -> Result<[ChessPiece; 64], FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+
+    let mut board = [ChessPiece::None; 64];
+    for (i, rank) in ranks.iter().enumerate() {
+        let y = 7 - i;
+        let mut x = 0;
+        for c in rank.chars() {
+            if let Some(n) = c.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                x += n as usize;
+                continue;
+            }
+            let piece = piece_from_letter(c).ok_or(FenError::BadPiece(c))?;
+            if x < 8 {
+                board[8*y + x] = piece;
+            }
+            x += 1;
+        }
+        if x != 8 {
+            return Err(FenError::BadRankLength(y + 1));
+        }
+    }
+
+    for col in [ChessColor::Wh, ChessColor::Bl] {
+        if board.iter().filter(|p| **p == ChessPiece::K(col)).count() > 1 {
+            return Err(FenError::TooManyKings(col));
+        }
+    }
+    return Ok(board);
+}
+
+impl ChessGame {
+    /**
+     * Sets up a game from a FEN string with all six fields, e.g.
+     * ```text
+     * rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1
+     * ```
+     *
+     * Malformed strings, such as ranks that don't add up to eight squares or
+     * more than one king per side, are rejected with a [FenError] describing
+     * the problem. The game has no move history before the given position.
+     */
+    pub fn from_fen(fen: &str) -> Result<ChessGame, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+
+        let board = parse_placement(fields[0])?;
+        let turn = match fields[1] {
+            "w" => ChessColor::Wh,
+            "b" => ChessColor::Bl,
+            _ => return Err(FenError::BadActiveColor),
+        };
+
+        let mut can_castle_k = [false; 2];
+        let mut can_castle_q = [false; 2];
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (rights, col) = match c {
+                    'K' => (&mut can_castle_k, ChessColor::Wh),
+                    'Q' => (&mut can_castle_q, ChessColor::Wh),
+                    'k' => (&mut can_castle_k, ChessColor::Bl),
+                    'q' => (&mut can_castle_q, ChessColor::Bl),
+                    _ => return Err(FenError::BadCastling),
+                };
+                if rights[col.index()] {
+                    return Err(FenError::BadCastling);
+                }
+                rights[col.index()] = true;
+            }
+        }
+
+        /* the pawn that passed the square belongs to the side that just moved */
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => match (parse_square(square), turn) {
+                (Some(t @ 40..=47), ChessColor::Wh)
+                | (Some(t @ 16..=23), ChessColor::Bl) => Some(t),
+                _ => return Err(FenError::BadEnPassant),
+            },
+        };
+
+        let halfmove = fields[4].parse().map_err(|_| FenError::BadCounter)?;
+        let fullmove = match fields[5].parse() {
+            Ok(0) | Err(_) => return Err(FenError::BadCounter),
+            Ok(fullmove) => fullmove,
+        };
+
+        let mut game = ChessGame::new();
+        game.board = board;
+        game.can_castle_k = can_castle_k;
+        game.can_castle_q = can_castle_q;
+        game.set_en_passant_target(en_passant);
+        game.halfmove_clock = halfmove;
+        game.fullmove_number = fullmove;
+        game.turn = turn;
+        game.update_state_to_move();
+
+        return Ok(game);
+    }
+
+    /**
+     * Returns the FEN string describing the current position, the inverse
+     * of [ChessGame::from_fen].
+     */
+    pub fn to_fen(&self) -> String {
+        let fields = self.fen_fields();
+        return format!("{} {} {} {} {} {}", fields.placement, fields.active_color,
+                       fields.castling, fields.en_passant, fields.halfmove, fields.fullmove);
+    }
+
+    /**
+     * Returns the fields of the FEN string describing the current position
+     * separately, which is useful for finding what differs between two
+     * positions.
+     */
+    pub fn fen_fields(&self) -> FenFields {
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let piece = self.board[8*rank + file];
+                if piece == ChessPiece::None {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    placement.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                placement.push(piece.letter());
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        let mut castling = String::new();
+        for (col, k, q) in [(ChessColor::Wh, 'K', 'Q'), (ChessColor::Bl, 'k', 'q')] {
+            if self.can_castle_k[col.index()] {
+                castling.push(k);
+            }
+            if self.can_castle_q[col.index()] {
+                castling.push(q);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        return FenFields {
+            placement,
+            active_color: if self.side_to_move() == ChessColor::Wh { 'w' } else { 'b' },
+            castling,
+            en_passant: match self.en_passant_target() {
+                Some(target) => square_name(target),
+                _ => "-".to_string(),
+            },
+            halfmove: self.halfmove_clock,
+            fullmove: self.fullmove_number,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn fen_fields() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.fen_fields(), FenFields {
+            placement: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR".to_string(),
+            active_color: 'w',
+            castling: "KQkq".to_string(),
+            en_passant: "-".to_string(),
+            halfmove: 0,
+            fullmove: 1,
+        });
+
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        game.make_move(&ChessMove::to(N(Bl), 62, 45)).unwrap();
+        game.make_move(&ChessMove::to(K(Wh), 4, 12)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 50, 34)).unwrap();
+        assert_eq!(game.fen_fields(), FenFields {
+            placement: "rnbqkb1r/pp1ppppp/5n2/2p5/4P3/8/PPPPKPPP/RNBQ1BNR".to_string(),
+            active_color: 'w',
+            castling: "kq".to_string(),
+            en_passant: "c6".to_string(),
+            halfmove: 0,
+            fullmove: 3,
+        });
+    }
+
+    #[test]
+    fn from_fen() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let game = ChessGame::from_fen(start).unwrap();
+        assert_eq!(game.get_board(), ChessGame::new().get_board());
+        assert_eq!(game.fen_fields(), ChessGame::new().fen_fields());
+        assert_eq!(game.get_legal_moves(&Wh).len(), 20);
+
+        let mut played = ChessGame::new();
+        played.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        played.make_move(&ChessMove::to(N(Bl), 62, 45)).unwrap();
+        played.make_move(&ChessMove::to(K(Wh), 4, 12)).unwrap();
+        played.make_move(&ChessMove::to(P(Bl), 50, 34)).unwrap();
+        let game = ChessGame::from_fen(
+            "rnbqkb1r/pp1ppppp/5n2/2p5/4P3/8/PPPPKPPP/RNBQ1BNR w kq c6 0 3").unwrap();
+        assert_eq!(game.fen_fields(), played.fen_fields());
+        assert_eq!(game.zobrist_hash(), played.zobrist_hash());
+        assert_eq!(game.get_legal_moves(&Wh), played.get_legal_moves(&Wh));
+
+        /* black to move and in check */
+        let game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 3 40").unwrap();
+        assert_eq!(game.turn(), Bl);
+        assert!(game.is_check());
+        assert_eq!(game.halfmove_clock, 3);
+        assert_eq!(game.fullmove_number, 40);
+    }
+
+    #[test]
+    fn to_fen() {
+        use ChessPiece::*;
+
+        assert_eq!(ChessGame::new().to_fen(),
+                   "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(ChessColor::Wh), 12, 28)).unwrap();
+        assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+        for fen in [
+            "rnbqkb1r/pp1ppppp/5n2/2p5/4P3/8/PPPPKPPP/RNBQ1BNR w kq c6 0 3",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Qk - 12 31",
+            "4k3/8/8/8/8/8/8/4R1K1 b - - 3 40",
+            "8/8/8/3pP3/8/8/8/k6K w - d6 0 60",
+        ] {
+            assert_eq!(ChessGame::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn from_fen_errors() {
+        use ChessColor::*;
+
+        for (fen, error) in [
+            ("", FenError::WrongFieldCount(0)),
+            ("8/8/8/8/8/8/8/8 w - - 0", FenError::WrongFieldCount(5)),
+            ("8/8/8/8/8/8/8 w - - 0 1", FenError::WrongRankCount(7)),
+            ("8/8/8/8/8/8/8/7 w - - 0 1", FenError::BadRankLength(1)),
+            ("8/8/8/8/8/8/ppppppppp/8 w - - 0 1", FenError::BadRankLength(2)),
+            ("44/8/8/8/8/8/8/9 w - - 0 1", FenError::BadPiece('9')),
+            ("8/8/8/8/8/8/8/7x w - - 0 1", FenError::BadPiece('x')),
+            ("k6k/8/8/8/8/8/8/K7 w - - 0 1", FenError::TooManyKings(Bl)),
+            ("8/8/8/8/8/8/8/KK6 w - - 0 1", FenError::TooManyKings(Wh)),
+            ("8/8/8/8/8/8/8/8 x - - 0 1", FenError::BadActiveColor),
+            ("8/8/8/8/8/8/8/8 w KK - 0 1", FenError::BadCastling),
+            ("8/8/8/8/8/8/8/8 w Kx - 0 1", FenError::BadCastling),
+            ("8/8/8/8/8/8/8/8 w - e3 0 1", FenError::BadEnPassant),
+            ("8/8/8/8/8/8/8/8 w - z9 0 1", FenError::BadEnPassant),
+            ("8/8/8/8/8/8/8/8 w - - x 1", FenError::BadCounter),
+            ("8/8/8/8/8/8/8/8 w - - 0 -1", FenError::BadCounter),
+            ("8/8/8/8/8/8/8/8 w - - 0 0", FenError::BadCounter),
+        ] {
+            assert_eq!(ChessGame::from_fen(fen).err(), Some(error), "{fen}");
+        }
+    }
+}
@@ -1,14 +1,81 @@
-#[derive(Debug,Copy,Clone,PartialEq,Eq)]
-enum ChessState {
+/* the explicit `return`s and `match` with a catch-all `_ => ()` are
+ * deliberate style choices throughout this crate */
+#![allow(clippy::needless_return)]
+#![allow(clippy::single_match)]
+#![allow(clippy::collapsible_match)]
+#![allow(clippy::manual_range_contains)]
+
+mod builder;
+mod bytes;
+mod chess960;
+mod describe;
+mod draw;
+mod endgame;
+mod eval;
+mod evasions;
+mod fen;
+mod macros;
+mod parse;
+mod pawns;
+mod perft;
+mod pgn;
+mod random;
+mod render;
+mod san;
+pub mod search;
+#[cfg(feature = "serde")]
+mod serialize;
+mod square;
+mod transposition;
+mod zobrist;
+
+pub use builder::{ChessMoveBuilder, MoveBuildError};
+pub use draw::{DrawReason, ADJUDICATION_HALFMOVES, ADJUDICATION_MATERIAL};
+pub use eval::{EvalParams, GamePhase};
+pub use evasions::CheckEvasions;
+pub use fen::{FenError, FenFields};
+pub use parse::{MoveParseError, ParseMoveError};
+pub use pawns::PawnStructure;
+pub use pgn::PgnError;
+pub use render::BoardPrintOptions;
+pub use search::{best_move, best_move_with_table};
+pub use square::{index_to_square, square_to_index, Square};
+pub use transposition::{Bound, TranspositionTable, TtEntry};
+
+use square::square_name;
+
+/**
+ * The status of the side to move, see [ChessGame::status].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum ChessState {
+    /* not in check and has a legal move */
     Normal,
+    /* in check but has a legal move */
     Check,
+    /* in check without a legal move */
+    Checkmate,
+    /* not in check but without a legal move */
+    Stalemate,
 }
 
 /**
- * Represents one color in chess. Commonly used as indices in arrays when
- * converted to [usize].
+ * Outcome of a game, see [ChessGame::result].
  */
 #[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum GameResult {
+    Ongoing,
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+/**
+ * Represents one color in chess. Commonly used as indices in arrays when
+ * converted with [ChessColor::index].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChessColor {
     Wh = 0,
     Bl,
@@ -19,6 +86,26 @@ impl ChessColor {
         return if *self == ChessColor::Wh { 1 } else { -1 };
     }
 
+    /**
+     * Returns the index of the color in arrays indexed by color, i.e. 0 for
+     * white and 1 for black.
+     */
+    pub fn index(&self) -> usize {
+        return *self as usize;
+    }
+
+    /**
+     * Gets the color with index `i` (see [ChessColor::index]), or [None] if
+     * `i` is out of range.
+     */
+    pub fn from_index(i: usize) -> Option<ChessColor> {
+        return match i {
+            0 => Some(ChessColor::Wh),
+            1 => Some(ChessColor::Bl),
+            _ => None,
+        };
+    }
+
     /**
      * Returns the opposite [ChessColor].
      */
@@ -26,6 +113,37 @@ impl ChessColor {
         use ChessColor::*;
         return if *self == Wh { Bl } else { Wh };
     }
+
+    /**
+     * Returns the rank (counted from 0, so `square / 8`) the pawns of this
+     * color start on, i.e. 1 for white and 6 for black.
+     */
+    pub fn pawn_start_rank(&self) -> usize {
+        return if *self == ChessColor::Wh { 1 } else { 6 };
+    }
+
+    /**
+     * Returns the rank (counted from 0, so `square / 8`) the pawns of this
+     * color promote on, i.e. 7 for white and 0 for black.
+     */
+    pub fn promotion_rank(&self) -> usize {
+        return if *self == ChessColor::Wh { 7 } else { 0 };
+    }
+
+    /**
+     * Returns [true] if a pawn of this color on `square` hasn't moved yet and
+     * may move two squares forward.
+     */
+    pub fn is_pawn_start_square(&self, square: usize) -> bool {
+        return square / 8 == self.pawn_start_rank();
+    }
+
+    /**
+     * Returns [true] if a pawn of this color moving to `square` promotes.
+     */
+    pub fn is_promotion_square(&self, square: usize) -> bool {
+        return square / 8 == self.promotion_rank();
+    }
 }
 
 /**
@@ -34,7 +152,8 @@ impl ChessColor {
  *
  * All pieces except the `None` piece have an associated color.
  */
-#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq,PartialOrd,Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChessPiece {
     None,
     P(ChessColor), /* pawn */
@@ -45,7 +164,122 @@ pub enum ChessPiece {
     K(ChessColor), /* king */
 }
 
+/**
+ * The type of a chess piece without its color, see [ChessPiece::kind].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PieceKind {
+    Pawn,
+    Rook,
+    Knight,
+    Bishop,
+    Queen,
+    King,
+}
+
+impl PieceKind {
+    /**
+     * Returns the [ChessPiece] of this kind with color `color`.
+     */
+    pub fn with_color(self, color: ChessColor) -> ChessPiece {
+        use PieceKind::*;
+        return match self {
+            Pawn => ChessPiece::P(color),
+            Rook => ChessPiece::R(color),
+            Knight => ChessPiece::N(color),
+            Bishop => ChessPiece::B(color),
+            Queen => ChessPiece::Q(color),
+            King => ChessPiece::K(color),
+        };
+    }
+
+    /* the letter used in algebraic notation, empty for pawns */
+    fn letter(&self) -> &'static str {
+        use PieceKind::*;
+        return match self {
+            Pawn => "",
+            Rook => "R",
+            Knight => "N",
+            Bishop => "B",
+            Queen => "Q",
+            King => "K",
+        };
+    }
+
+    /* material value in centipawns, kings are not counted */
+    fn value(&self) -> i32 {
+        use PieceKind::*;
+        return match self {
+            Pawn => 100,
+            Rook => 500,
+            Knight | Bishop => 300,
+            Queen => 900,
+            King => 0,
+        };
+    }
+}
+
+const ROOK_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+const QUEEN_DIRECTIONS: [(isize, isize); 8] = [
+    (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1),
+];
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (1, 2), (-1, 2), (1, -2), (-1, -2), (2, 1), (-2, 1), (2, -1), (-2, -1),
+];
+const WHITE_PAWN_CAPTURES: [(isize, isize); 2] = [(1, 1), (-1, 1)];
+const BLACK_PAWN_CAPTURES: [(isize, isize); 2] = [(1, -1), (-1, -1)];
+
 impl ChessPiece {
+    /**
+     * Gets the [PieceKind] of the [ChessPiece] if it isn't [ChessPiece::None].
+     * Otherwise return [None].
+     */
+    pub fn kind(&self) -> Option<PieceKind> {
+        use ChessPiece::*;
+        return match self {
+            P(_) => Some(PieceKind::Pawn),
+            R(_) => Some(PieceKind::Rook),
+            N(_) => Some(PieceKind::Knight),
+            B(_) => Some(PieceKind::Bishop),
+            Q(_) => Some(PieceKind::Queen),
+            K(_) => Some(PieceKind::King),
+            None => Option::None,
+        };
+    }
+
+    /**
+     * Returns [true] for pieces that slide any number of squares along their
+     * directions, i.e. rooks, bishops and queens.
+     */
+    pub fn is_slider(&self) -> bool {
+        use ChessPiece::*;
+        return matches!(self, R(_) | B(_) | Q(_));
+    }
+
+    /**
+     * Returns the `(dx, dy)` offsets the piece moves along, with `dy` towards
+     * higher ranks. Sliders (see [ChessPiece::is_slider]) repeat their
+     * offsets until blocked, while knights and kings step once. Pawns return
+     * their capture offsets, which depend on the color, and
+     * [ChessPiece::None] returns no offsets.
+     */
+    pub fn directions(&self) -> &'static [(isize, isize)] {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        return match self {
+            P(Wh) => &WHITE_PAWN_CAPTURES,
+            P(Bl) => &BLACK_PAWN_CAPTURES,
+            R(_) => &ROOK_DIRECTIONS,
+            N(_) => &KNIGHT_OFFSETS,
+            B(_) => &BISHOP_DIRECTIONS,
+            Q(_) | K(_) => &QUEEN_DIRECTIONS,
+            None => &[],
+        };
+    }
+
     /**
      * Gets the color of the [ChessPiece] if it isn't [ChessPiece::None].
      * Otherwise return [None].
@@ -64,32 +298,143 @@ impl ChessPiece {
     }
 
     fn str(&self) -> String {
-        use ChessPiece::*;
+        return String::from(match self.kind() {
+            Some(kind) => kind.letter(),
+            _ => "_",
+        });
+    }
 
-        return String::from(match self {
-            P(_) => "",
-            R(_) => "R",
-            N(_) => "N",
-            B(_) => "B",
-            Q(_) => "Q",
-            K(_) => "K",
-            None => "_",
+    fn is_pawn(&self) -> bool {
+        return self.kind() == Some(PieceKind::Pawn);
+    }
+
+    /* the same piece with the other color */
+    fn flip_color(&self) -> ChessPiece {
+        return match (self.kind(), self.color()) {
+            (Some(kind), Some(col)) => kind.with_color(col.opposite()),
+            _ => ChessPiece::None,
+        };
+    }
+
+    /* upper case for white and lower case for black, `.` for empty squares */
+    fn letter(&self) -> char {
+        let c = match self.kind() {
+            Some(PieceKind::Pawn) => 'P',
+            Some(kind) => kind.letter().chars().next().unwrap(),
+            _ => return '.',
+        };
+        return if self.color() == Some(ChessColor::Bl) { c.to_ascii_lowercase() } else { c };
+    }
+
+    /* the chess symbol from Unicode, [None] for empty squares */
+    fn glyph(&self) -> Option<char> {
+        use ChessPiece::*;
+        use ChessColor::*;
+        return Some(match self {
+            K(Wh) => '♔', Q(Wh) => '♕', R(Wh) => '♖', B(Wh) => '♗', N(Wh) => '♘', P(Wh) => '♙',
+            K(Bl) => '♚', Q(Bl) => '♛', R(Bl) => '♜', B(Bl) => '♝', N(Bl) => '♞', P(Bl) => '♟',
+            None => return Option::None,
         });
     }
 
+    /* material value in centipawns, kings are not counted */
+    fn value(&self) -> i32 {
+        return self.kind().map_or(0, |kind| kind.value());
+    }
+
     fn to(&self, origin: usize, target: usize) -> ChessMove {
-        return ChessMove::to(self.clone(), origin, target);
+        return ChessMove::to(*self, origin, target);
     }
 
     fn captures(&self, origin: usize, target: usize, captures: ChessPiece) -> ChessMove {
-        return ChessMove::captures(self.clone(), origin, target, captures);
+        return ChessMove::captures(*self, origin, target, captures);
     }
 }
 
 /**
- * Representation of one move in chess.
+ * Annotation glyph attached to a move, as used in annotated PGN. Each of the
+ * symbolic variants corresponds to one of the NAGs `$1` to `$6`, any other
+ * numeric annotation glyph is stored as [MoveAnnotation::Nag].
  */
 #[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveAnnotation {
+    Good,        /* ! */
+    Mistake,     /* ? */
+    Brilliant,   /* !! */
+    Blunder,     /* ?? */
+    Interesting, /* !? */
+    Dubious,     /* ?! */
+    Nag(u8),
+}
+
+impl MoveAnnotation {
+    /**
+     * Creates a [MoveAnnotation] from a numeric annotation glyph, preferring
+     * the symbolic variants for `$1` to `$6`.
+     */
+    pub fn from_nag(nag: u8) -> MoveAnnotation {
+        use MoveAnnotation::*;
+        return match nag {
+            1 => Good,
+            2 => Mistake,
+            3 => Brilliant,
+            4 => Blunder,
+            5 => Interesting,
+            6 => Dubious,
+            _ => Nag(nag),
+        };
+    }
+
+    /**
+     * Returns the numeric annotation glyph of the annotation.
+     */
+    pub fn nag(&self) -> u8 {
+        use MoveAnnotation::*;
+        return match self {
+            Good => 1,
+            Mistake => 2,
+            Brilliant => 3,
+            Blunder => 4,
+            Interesting => 5,
+            Dubious => 6,
+            Nag(nag) => *nag,
+        };
+    }
+
+    /**
+     * Returns the annotation as it is written directly after a move, i.e. a
+     * symbol like `!?` or a NAG like ` $14` (separated by a space).
+     */
+    pub fn symbol(&self) -> String {
+        use MoveAnnotation::*;
+        return String::from(match self {
+            Good => "!",
+            Mistake => "?",
+            Brilliant => "!!",
+            Blunder => "??",
+            Interesting => "!?",
+            Dubious => "?!",
+            Nag(nag) => return format!(" ${nag}"),
+        });
+    }
+}
+
+/**
+ * The number of moves space is reserved for up front during move generation.
+ * Most positions have fewer pseudo-legal moves than this, so the move lists
+ * rarely have to grow.
+ */
+pub const MOVE_LIST_CAPACITY: usize = 48;
+
+/**
+ * Representation of one move in chess.
+ *
+ * Equality and hashing ignore [ChessMove::annotation], so an annotated move
+ * still compares equal to the corresponding legal move.
+ */
+#[derive(Debug,Copy,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChessMove {
     /**
      * The piece which moves.
@@ -116,6 +461,54 @@ pub struct ChessMove {
     /* these could really be one enum but whatever */
     pub en_passant: bool,
     pub castles: bool,
+
+    /**
+     * An optional annotation glyph, [None] unless set using
+     * [ChessMove::annotated].
+     */
+    pub annotation: Option<MoveAnnotation>,
+}
+
+impl ChessMove {
+    /* every field except the annotation, in the order moves are sorted by */
+    fn key(&self) -> (usize, usize, ChessPiece, bool, bool, ChessPiece, ChessPiece) {
+        return (self.origin, self.target, self.promotes, self.castles,
+                self.en_passant, self.piece, self.captures);
+    }
+}
+
+/**
+ * Moves are ordered by origin square, then target square, then promotion
+ * piece (in the order [ChessPiece] declares them), then castling and en
+ * passant (`false` first). The moving and captured pieces only break ties
+ * between moves that couldn't both be legal in the same position, so that
+ * the order is total and agrees with [PartialEq]. Annotations are ignored
+ * like they are for equality.
+ */
+impl Ord for ChessMove {
+    fn cmp(&self, other: &ChessMove) -> std::cmp::Ordering {
+        return self.key().cmp(&other.key());
+    }
+}
+
+impl PartialOrd for ChessMove {
+    fn partial_cmp(&self, other: &ChessMove) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl PartialEq for ChessMove {
+    fn eq(&self, other: &ChessMove) -> bool {
+        return self.key() == other.key();
+    }
+}
+
+impl Eq for ChessMove {}
+
+impl std::hash::Hash for ChessMove {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
 }
 
 
@@ -127,28 +520,54 @@ impl ChessMove {
             promotes: ChessPiece::None,
             en_passant: false,
             castles: false,
+            annotation: None,
         };
     }
 
     fn captures(piece: ChessPiece, origin: usize, target: usize, captures: ChessPiece) -> ChessMove {
         return ChessMove {
             piece, origin, target,
-            captures: captures,
+            captures,
             promotes: ChessPiece::None,
             en_passant: false,
             castles: false,
+            annotation: None,
         };
     }
 
     /**
-     * Returns the move in algebraic notation (sort of).
+     * Returns a copy of the move carrying `annotation`.
+     */
+    pub fn annotated(mut self, annotation: MoveAnnotation) -> ChessMove {
+        self.annotation = Some(annotation);
+        return self;
+    }
+
+    /**
+     * Returns [true] if the move castles on the queenside, i.e. the king ends
+     * up on the c-file. The king doesn't have to move left for that in
+     * Chess960, see [ChessGame::from_chess960].
+     */
+    pub fn castles_queenside(&self) -> bool {
+        return self.castles && self.target % 8 < 4;
+    }
+
+    /**
+     * Returns the move in algebraic notation (sort of), followed by the
+     * annotation glyph if there is one.
      *
      * Does not contain information about checks, and contains redundant
-     * information about piece locations.
+     * information about piece locations. Use [ChessMove::san] for standard
+     * notation.
      */
     pub fn algebraic(&self) -> String {
+        let annotation = match self.annotation {
+            Some(annotation) => annotation.symbol(),
+            _ => String::from(""),
+        };
+
         if self.castles {
-            return String::from(if self.target as isize - self.origin as isize == -2 {
+            return format!("{}{annotation}", if self.castles_queenside() {
                 "O-O-O"
             } else {
                 "O-O"
@@ -156,10 +575,8 @@ impl ChessMove {
         }
 
         let piece = self.piece.str();
-        let file1 = char::from(b'a' + (self.origin % 8) as u8);
-        let rank1 = self.origin / 8 + 1;
-        let file2 = char::from(b'a' + (self.target % 8) as u8);
-        let rank2 = self.target / 8 + 1;
+        let origin = square_name(self.origin);
+        let target = square_name(self.target);
         let captures = if self.captures != ChessPiece::None {"x"} else {""};
         let ep = if self.en_passant {" e.p."} else {""};
         let promotes = if self.promotes == ChessPiece::None {
@@ -168,29 +585,257 @@ impl ChessMove {
             format!("({})", self.promotes.str())
         };
 
-        return format!("{piece}{file1}{rank1}{captures}{file2}{rank2}{promotes}{ep}");
+        return format!("{piece}{origin}{captures}{target}{promotes}{ep}{annotation}");
+    }
+}
+
+/**
+ * Reasons a move can be rejected by [ChessGame::make_move].
+ */
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum MoveError {
+    /** The moved piece doesn't belong to the side to move. */
+    NotYourTurn,
+    /** The move captures a piece of the side making it. */
+    SelfCapture,
+    /** The move captures a king, which never happens in a legal game. */
+    IllegalKingCapture,
+    /** The move is not among the legal moves of the side to move. */
+    IllegalMove,
+    /** The moved piece is not on the origin square of the move. */
+    WrongPiece,
+    /** A square is not on the board. */
+    OutOfBounds,
+    /** The move at `index` of a move list failed with `error`. */
+    InMoveList { index: usize, error: Box<MoveError> },
+}
+
+/**
+ * Breakdown of the material on the board, see [ChessGame::material_detail].
+ * All count arrays are indexed by [ChessColor::index].
+ */
+#[derive(Debug,Copy,Clone,Default,PartialEq,Eq)]
+pub struct MaterialDetail {
+    pub pawns: [u32; 2],
+    pub rooks: [u32; 2],
+    pub knights: [u32; 2],
+    pub bishops: [u32; 2],
+    pub queens: [u32; 2],
+    pub kings: [u32; 2],
+    /**
+     * The net material in centipawns, positive when white is ahead. Pawns are
+     * worth 100, knights and bishops 300, rooks 500 and queens 900. Kings are
+     * not counted.
+     */
+    pub balance: i32,
+}
+
+/**
+ * Gets the square `dx` files and `dy` ranks away from square `i`, where
+ * positive `dx` goes towards the h-file and positive `dy` towards rank 8.
+ * Returns [None] if that would be off the board, or if `i` is not a square.
+ */
+pub fn neighbor(i: usize, dx: isize, dy: isize) -> Option<usize> {
+    if i >= 64 {
+        return None;
+    }
+
+    let x = (i % 8) as isize + dx;
+    let y = (i / 8) as isize + dy;
+
+    return if 0 <= x && x <= 7 && 0 <= y && y <= 7 {
+        Some((8*y+x) as usize)
+    } else {
+        None
+    };
+}
+
+/* checks if any piece of color `by` attacks `square` on `board`, looking
+ * outwards from the square instead of generating moves */
+fn square_attacked(board: &[ChessPiece; 64], square: usize, by: ChessColor) -> bool {
+    use ChessPiece::*;
+
+    let attacked_by = |dx: isize, dy: isize, piece: ChessPiece| {
+        return neighbor(square, dx, dy).is_some_and(|t| board[t] == piece);
+    };
+
+    /* pawns attack diagonally forward, so look diagonally backward */
+    if attacked_by(1, -by.dir(), P(by)) || attacked_by(-1, -by.dir(), P(by)) {
+        return true;
+    }
+
+    for (dx, dy) in KNIGHT_OFFSETS {
+        if attacked_by(dx, dy, N(by)) {
+            return true;
+        }
+    }
+
+    for (dx, dy) in QUEEN_DIRECTIONS {
+        if attacked_by(dx, dy, K(by)) {
+            return true;
+        }
+
+        /* first piece along the line, which can only attack if it slides in
+         * this direction */
+        let mut pos = square;
+        while let Some(t) = neighbor(pos, dx, dy) {
+            pos = t;
+            if board[t] == None {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if board[t] == Q(by)
+                || (diagonal && board[t] == B(by))
+                || (!diagonal && board[t] == R(by)) {
+                return true;
+            }
+            break;
+        }
+    }
+
+    return false;
+}
+
+/* everything needed to restore a position, see `ChessGame::position` */
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+struct Position {
+    board: [ChessPiece; 64],
+    can_castle_k: [bool; 2],
+    can_castle_q: [bool; 2],
+    en_passant_loc: [Option<(usize, usize)>; 2],
+    turn: ChessColor,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl Position {
+    /* the same position for the purpose of repetitions, i.e. everything
+     * except the move counters */
+    fn repeats(&self, other: &Position) -> bool {
+        return self.board == other.board
+            && self.can_castle_k == other.can_castle_k
+            && self.can_castle_q == other.can_castle_q
+            && self.en_passant_loc == other.en_passant_loc
+            && self.turn == other.turn;
+    }
+}
+
+/* a played move and the position it was played in */
+#[derive(Debug,Copy,Clone)]
+struct HistoryEntry {
+    mv: ChessMove,
+    position: Position,
+}
+
+/* moves the pieces on `board` according to `mv`, without any validation.
+ * `rook_file` is the file of the rook castling with `mv`, if it castles */
+fn apply_board_effects(board: &mut [ChessPiece; 64], mv: &ChessMove, rook_file: usize) {
+    if mv.castles {
+        /* the king and rook can swap squares in Chess960, so both are lifted
+         * off the board first */
+        let home = 8*(mv.target/8);
+        let rook = board[home + rook_file];
+        board[home + rook_file] = ChessPiece::None;
+        board[mv.origin] = ChessPiece::None;
+        board[home + if mv.castles_queenside() {3} else {5}] = rook;
+        board[mv.target] = mv.piece;
+        return;
+    }
+
+    board[mv.target] = if mv.promotes == ChessPiece::None {
+        mv.piece
+    } else {
+        mv.promotes
+    };
+    board[mv.origin] = ChessPiece::None;
+
+    if mv.en_passant {
+        /* the captured pawn is on the origin rank, next to the origin */
+        board[8*(mv.origin/8) + mv.target%8] = ChessPiece::None;
+    }
+}
+
+/* calls `visit` with every square holding a piece of color `by` that attacks
+ * `square`, without allocating */
+fn visit_square_attackers(board: &[ChessPiece; 64], square: usize, by: ChessColor,
+                          mut visit: impl FnMut(usize)) {
+    use ChessPiece::*;
+
+    let mut attacked_by = |dx: isize, dy: isize, piece: ChessPiece| {
+        if let Some(t) = neighbor(square, dx, dy) {
+            if board[t] == piece {
+                visit(t);
+            }
+        }
+    };
+
+    attacked_by(1, -by.dir(), P(by));
+    attacked_by(-1, -by.dir(), P(by));
+    for (dx, dy) in KNIGHT_OFFSETS {
+        attacked_by(dx, dy, N(by));
+    }
+    for (dx, dy) in QUEEN_DIRECTIONS {
+        attacked_by(dx, dy, K(by));
+    }
+
+    for (dx, dy) in QUEEN_DIRECTIONS {
+        let mut pos = square;
+        while let Some(t) = neighbor(pos, dx, dy) {
+            pos = t;
+            if board[t] == None {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if board[t] == Q(by)
+                || (diagonal && board[t] == B(by))
+                || (!diagonal && board[t] == R(by)) {
+                visit(t);
+            }
+            break;
+        }
     }
 }
 
+/* every square holding a piece of color `by` that attacks `square` */
+fn square_attackers(board: &[ChessPiece; 64], square: usize, by: ChessColor) -> Vec<usize> {
+    let mut out = Vec::new();
+    visit_square_attackers(board, square, by, |t| out.push(t));
+    return out;
+}
+
 /**
  * Representation of one game of chess
  */
 #[derive(Debug,Clone)]
 pub struct ChessGame {
     board: [ChessPiece; 64],
-    temp_board: [ChessPiece; 64],
     can_castle_k: [bool; 2],
     can_castle_q: [bool; 2],
     can_castle_now_k: [bool; 2],
     can_castle_now_q: [bool; 2],
+    /* the files the rooks castle from, the corners except in Chess960 */
+    rook_file_k: usize,
+    rook_file_q: usize,
     en_passant_loc: [Option<(usize, usize)>; 2],
     next_moves: [Vec<ChessMove>; 2],
+    /* the pseudo-legal moves of both sides except castling, ordered by
+     * origin square, as found on `pseudo_board` with `pseudo_en_passant`.
+     * `update_pseudo_moves` keeps them up to date without regenerating
+     * every piece's moves after each move */
+    pseudo_moves: [Vec<ChessMove>; 2],
+    pseudo_board: [ChessPiece; 64],
+    pseudo_en_passant: [Option<(usize, usize)>; 2],
     state: ChessState,
-    /**
-     * The color whose turn it currently is. Can be modified in place, but the
-     * helper function [ChessGame::switch_turn] exists to swap it.
-     */
-    pub turn: ChessColor,
+    /* plies since the last capture or pawn move */
+    halfmove_clock: u32,
+    /* starts at 1 and increases after every move by black */
+    fullmove_number: u32,
+    history: Vec<HistoryEntry>,
+    /* the color whose turn it currently is */
+    turn: ChessColor,
+    /* a move by `turn` was played with `apply_move` and the turn hasn't been
+     * switched yet, so the other side is the one that has to move next */
+    moved_unswitched: bool,
 }
 
 impl ChessGame {
@@ -214,22 +859,40 @@ impl ChessGame {
 
         let mut game = ChessGame {
             board,
-            temp_board: [None; 64],
             can_castle_k: [true; 2],
             can_castle_q: [true; 2],
             can_castle_now_k: [false; 2],
             can_castle_now_q: [false; 2],
+            rook_file_k: 7,
+            rook_file_q: 0,
             en_passant_loc: [Option::None; 2],
             next_moves: [Vec::new(), Vec::new()],
+            pseudo_moves: [Vec::new(), Vec::new()],
+            pseudo_board: [None; 64],
+            pseudo_en_passant: [Option::None; 2],
             turn: Wh,
-            state: ChessState::Normal
+            state: ChessState::Normal,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: Vec::new(),
+            moved_unswitched: false,
         };
-        /* HACK: calculate initial game state by doing nothing */
-        game.apply_move(&ChessMove::to(None, 16, 16));
+        game.update_state();
 
         return game;
     }
 
+    /**
+     * Creates a game from the standard starting position with `turn` to move,
+     * eg. for odds games where black moves first.
+     */
+    pub fn new_with_turn(turn: ChessColor) -> ChessGame {
+        let mut game = ChessGame::new();
+        game.turn = turn;
+        game.update_state_to_move();
+        return game;
+    }
+
     /**
      * Returns an immutable reference to the current board. Index 0 is `a1` and
      * the array follows rank-major order up to `h8`.
@@ -238,11 +901,44 @@ impl ChessGame {
         return &self.board;
     }
 
+    /**
+     * Returns the piece on the square at `file` (`'a'` to `'h'`) and `rank`
+     * (1 to 8). Empty squares and squares off the board both give [None],
+     * so the result is never `Some(ChessPiece::None)`.
+     */
+    pub fn piece_at(&self, file: char, rank: u8) -> Option<ChessPiece> {
+        return self.piece_at_index(square_to_index(file, rank)?);
+    }
+
+    /**
+     * Returns the piece on the square with board index `i` (see
+     * [ChessGame::get_board]), or [None] if the square is empty or `i` is
+     * off the board, like [ChessGame::piece_at].
+     */
+    pub fn piece_at_index(&self, i: usize) -> Option<ChessPiece> {
+        return self.board.get(i).copied().filter(|piece| *piece != ChessPiece::None);
+    }
+
+    /**
+     * Lists the squares where the board differs from the board of `other` as
+     * `(square, piece here, piece in other)`, ordered by square. Empty if the
+     * boards are the same, regardless of the rest of the game state.
+     */
+    pub fn board_diff(&self, other: &ChessGame) -> Vec<(usize, ChessPiece, ChessPiece)> {
+        return (0..64).filter(|i| self.board[*i] != other.board[*i])
+                      .map(|i| (i, self.board[i], other.board[i]))
+                      .collect();
+    }
+
     /**
      * Load a custom board into the game. Disables castling for both players,
      * which can be turned back on using [ChessGame::set_castle_eligibility] or
-     * [ChessGame::set_all_castle_eligibility]. Note that castling only works
-     * correctly when the king is placed in its normal location.
+     * [ChessGame::set_all_castle_eligibility].
+     *
+     * The king doesn't have to be on its normal square to castle. It can be
+     * anywhere on its back rank between the rooks in the corners, and
+     * castling then works like in Chess960 (see [ChessGame::from_chess960]):
+     * the king ends up on the c- or g-file and the rook next to it.
      */
     pub fn load_board(&mut self, board: [ChessPiece; 64]) {
         self.board = board;
@@ -250,9 +946,31 @@ impl ChessGame {
         /* disable castling after loading arbitrary boards */
         self.can_castle_k = [false; 2];
         self.can_castle_q = [false; 2];
+        self.rook_file_k = 7;
+        self.rook_file_q = 0;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
+        self.en_passant_loc = [None; 2];
+        self.history.clear();
+        self.update_state_to_move();
+    }
 
-        /* HACK: calculate game state by doing nothing */
-        self.apply_move(&ChessMove::to(ChessPiece::None, 16, 16));
+    /**
+     * Loads a board with only the listed `pieces`, given as pairs of square
+     * and piece, like [ChessGame::load_board] does. Fails with
+     * [MoveError::OutOfBounds] without changing anything if a square is off
+     * the board. Later pieces replace earlier ones on the same square.
+     */
+    pub fn place_pieces(&mut self, pieces: &[(usize, ChessPiece)]) -> Result<(), MoveError> {
+        let mut board = [ChessPiece::None; 64];
+        for (square, piece) in pieces {
+            if *square >= 64 {
+                return Err(MoveError::OutOfBounds);
+            }
+            board[*square] = *piece;
+        }
+        self.load_board(board);
+        return Ok(());
     }
 
     /**
@@ -265,19 +983,18 @@ impl ChessGame {
      */
     pub fn set_castle_eligibility(&mut self, side: &ChessColor, queens: bool, state: bool) {
         if queens {
-            self.can_castle_q[*side as usize] = state;
+            self.can_castle_q[side.index()] = state;
         } else {
-            self.can_castle_k[*side as usize] = state;
+            self.can_castle_k[side.index()] = state;
         }
 
-        /* HACK: update game state by doing nothing */
-        self.apply_move(&ChessMove::to(ChessPiece::None, 0, 0));
+        self.update_state();
     }
 
     /**
      * Sets the castle eligibility for both black and white on `kings` and
-     * `queens` side. The arguments are arrays where the indices are
-     * [ChessColor]s as [usize].
+     * `queens` side. The arguments are arrays indexed by
+     * [ChessColor::index].
      *
      * This could be useful after running [ChessGame::load_board] with custom
      * boards.
@@ -286,66 +1003,165 @@ impl ChessGame {
         self.can_castle_q = queens;
         self.can_castle_k = kings;
 
-        /* HACK: update game state by doing nothing */
-        self.apply_move(&ChessMove::to(ChessPiece::None, 0, 0));
+        self.update_state();
     }
 
     /**
-     * Switches the turn.
+     * Returns the color whose turn it currently is.
      */
-    pub fn switch_turn(&mut self) {
-        self.turn = self.turn.opposite();
+    pub fn turn(&self) -> ChessColor {
+        return self.turn;
     }
 
     /**
-     * Plays the provided move (`mv`). This does not automatically switch the
-     * turn, which must be done using [ChessGame::switch_turn].
+     * Switches the turn. Returns the game so calls can be chained.
      */
-    pub fn apply_move(&mut self, mv: &ChessMove) -> bool {
-        return self.apply_move_internal(mv, true);
+    pub fn switch_turn(&mut self) -> &mut ChessGame {
+        self.turn = self.turn.opposite();
+        self.moved_unswitched = false;
+        self.update_status();
+        return self;
     }
 
-    fn apply_move_internal(&mut self, mv: &ChessMove, real: bool) -> bool {
-        /* HACK: Allow moves of None to update game state */
-        if mv.piece != ChessPiece::None {
-            if mv.piece != self.board[mv.origin] {
-                eprintln!("Illegal move: board:{:?} move:{:?}",
-                          self.board[mv.origin], mv);
-                return false;
-            }
+    /**
+     * Returns the color that has to make the next move. This is usually
+     * [ChessGame::turn], except right after [ChessGame::apply_move] and
+     * before [ChessGame::switch_turn], when it is already the side replying
+     * to the move. [ChessGame::is_check], [ChessGame::is_ended],
+     * [ChessGame::is_checkmate] and [ChessGame::is_stalemate] all refer to
+     * this side, so they can be asked either before or after switching.
+     * The position is the same either way: the FEN string, hashes, byte
+     * encoding and repetition checks all use this side as the one to move.
+     */
+    pub fn side_to_move(&self) -> ChessColor {
+        return if self.moved_unswitched { self.turn.opposite() } else { self.turn };
+    }
 
-            self.board[mv.target] = if mv.promotes == ChessPiece::None {
-                mv.piece
-            } else {
-                mv.promotes
-            };
-            self.board[mv.origin] = ChessPiece::None;
+    /* a copy of the game with the turn handed to the side to move, so that
+     * the moves of that side can be played on it with `make_move` */
+    pub(crate) fn with_side_to_move(&self) -> ChessGame {
+        let mut game = self.clone();
+        if game.moved_unswitched {
+            game.switch_turn();
+        }
+        return game;
+    }
 
-            if mv.en_passant {
-                self.board[mv.target.wrapping_add_signed(-8*self.turn.dir())]
-                    = ChessPiece::None;
+    /**
+     * Creates a game from the starting position by playing all `moves` in
+     * order, switching the turn after each. If a move can't be played the
+     * error is wrapped in [MoveError::InMoveList] along with its index.
+     */
+    pub fn from_move_list(moves: &[ChessMove]) -> Result<ChessGame, MoveError> {
+        let mut game = ChessGame::new();
+        for (index, mv) in moves.iter().enumerate() {
+            if let Err(error) = game.make_move(mv) {
+                return Err(MoveError::InMoveList { index, error: Box::new(error) });
             }
+        }
+        return Ok(game);
+    }
 
-            if mv.castles {
-                let queens = mv.target as isize - mv.origin as isize == -2;
-                let rook_origin = mv.origin.wrapping_add_signed(if queens {-4} else {3});
-                let rook_target = (mv.target + mv.origin)/2;
-                self.board[rook_target] = self.board[rook_origin];
-                self.board[rook_origin] = ChessPiece::None;
-            }
+    /**
+     * Plays the provided move (`mv`) if it is legal for the side to move and
+     * switches the turn. After [ChessGame::apply_move] without
+     * [ChessGame::switch_turn] the turn is switched first, so the move has to
+     * be one of the replying side's.
+     */
+    pub fn make_move(&mut self, mv: &ChessMove) -> Result<(), MoveError> {
+        let side = self.side_to_move();
+        if mv.piece.color() != Some(side) {
+            return Err(MoveError::NotYourTurn);
+        }
+        if mv.captures.color() == Some(side) {
+            return Err(MoveError::SelfCapture);
+        }
+        if let ChessPiece::K(_) = mv.captures {
+            return Err(MoveError::IllegalKingCapture);
+        }
+        if !self.next_moves[side.index()].contains(mv) {
+            return Err(MoveError::IllegalMove);
         }
 
-        /* ignore lasting effects of non-real moves
-         * eg. calls from `apply_temp_move` */
-        if !real {
-            return true;
+        if self.moved_unswitched {
+            self.switch_turn();
+        }
+        self.apply_move_internal(mv);
+        self.switch_turn();
+        return Ok(());
+    }
+
+    /**
+     * Returns a copy of the game with `mv` played like [ChessGame::make_move]
+     * does, leaving `self` untouched. Useful for exploring moves without
+     * mutating, eg. `moves.iter().map(|mv| game.clone_with_move(mv))`.
+     */
+    pub fn clone_with_move(&self, mv: &ChessMove) -> Result<ChessGame, MoveError> {
+        let mut game = self.clone();
+        game.make_move(mv)?;
+        return Ok(game);
+    }
+
+    /**
+     * Plays the provided move (`mv`). This does not automatically switch the
+     * turn, which must be done using [ChessGame::switch_turn], so the usual
+     * order is `apply_move`, `switch_turn`, then asking about the new
+     * position. The state of the game is up to date right after the move
+     * though, see [ChessGame::side_to_move]. [ChessGame::make_move] does
+     * both steps at once and checks the move first.
+     *
+     * Fails with [MoveError::OutOfBounds] if a square is off the board and
+     * with [MoveError::WrongPiece] if the moved piece is not on its origin
+     * square, without changing anything. Legality is not checked otherwise.
+     */
+    pub fn apply_move(&mut self, mv: &ChessMove) -> Result<(), MoveError> {
+        if mv.origin >= 64 || mv.target >= 64 {
+            return Err(MoveError::OutOfBounds);
+        }
+        if mv.piece == ChessPiece::None || mv.piece != self.board[mv.origin] {
+            return Err(MoveError::WrongPiece);
+        }
+        self.apply_move_internal(mv);
+        return Ok(());
+    }
+
+    /**
+     * DANGER: plays `mv` without ANY checks. The move doesn't have to be
+     * legal, the piece doesn't have to be on its origin square and it doesn't
+     * have to be its turn, and the turn is not switched. Castling, en passant
+     * and promotion are carried out as described by the move, castle rights,
+     * clocks and history are updated as for any other move, and then all
+     * cached state is recomputed from the resulting board.
+     *
+     * This is meant for setting up deliberately illegal positions and for
+     * variants with their own rules. The game can easily end up in a state
+     * no legal game could reach, so use [ChessGame::make_move] for anything
+     * else.
+     */
+    pub fn force_apply(&mut self, mv: &ChessMove) {
+        self.apply_move_internal(mv);
+    }
+
+    /* plays `mv` and updates all state, see `force_apply` */
+    pub(crate) fn apply_move_internal(&mut self, mv: &ChessMove) {
+        /* forced moves of nothing leave the board and history alone */
+        if mv.piece != ChessPiece::None {
+            /* the side that moves is the side to move in the stored
+             * position, even if the turn was never switched */
+            let mut position = self.position();
+            position.turn = mv.piece.color().unwrap();
+            self.history.push(HistoryEntry { mv: *mv, position });
+
+            let rook_file = self.rook_file(mv);
+            apply_board_effects(&mut self.board, mv, rook_file);
         }
 
         /* check which squares can en passant next turn */
-        let en_passant_target = (mv.origin + mv.target) / 2;
-        /* jank way to check for pawn */
-        if mv.piece.str() == "" &&
-            (mv.origin as isize - mv.target as isize).abs() == 16 {
+        let en_passant_target = match (mv.piece, mv.origin.abs_diff(mv.target)) {
+            (ChessPiece::P(col), 16) => ChessGame::en_passant_square_for(mv.target, col),
+            _ => None,
+        };
+        if let Some(en_passant_target) = en_passant_target {
             match self.step_real(mv.target, 1, 0) {
                 Some(loc) => self.en_passant_loc[0]
                     = Some((loc, en_passant_target)),
@@ -360,77 +1176,268 @@ impl ChessGame {
             self.en_passant_loc = [None; 2];
         }
 
+        /* captures and pawn moves are irreversible and reset the fifty-move
+         * clock. Castling and other moves losing castle rights can't be
+         * undone either, but they still count towards the fifty moves, and
+         * positions on either side of them never repeat because the castle
+         * rights differ (see `Position::repeats`). */
+        if mv.piece != ChessPiece::None {
+            if mv.captures != ChessPiece::None || mv.piece.is_pawn() {
+                self.halfmove_clock = 0;
+            } else {
+                self.halfmove_clock += 1;
+            }
+        }
+
         /* check castle eligibility */
         match mv.piece {
             ChessPiece::K(side) => {
-                self.can_castle_k[side as usize] = false;
-                self.can_castle_q[side as usize] = false;
+                self.can_castle_k[side.index()] = false;
+                self.can_castle_q[side.index()] = false;
             },
-            ChessPiece::R(side) => {
-                if mv.origin == 0 || mv.origin == 56 {
-                    self.can_castle_q[side as usize] = false;
-                } else if mv.origin == 7 || mv.origin == 63 {
-                    self.can_castle_k[side as usize] = false;
-                }
-            }
+            ChessPiece::R(side) => self.clear_rook_castle_right(side, mv.origin),
             _ => (),
         }
+        /* a captured rook can't castle either */
+        if let ChessPiece::R(side) = mv.captures {
+            self.clear_rook_castle_right(side, mv.target);
+        }
+
+        if mv.piece.color() == Some(ChessColor::Bl) {
+            self.fullmove_number += 1;
+        }
+
+        if mv.piece != ChessPiece::None {
+            self.moved_unswitched = mv.piece.color() == Some(self.turn);
+        }
+
+        self.update_state();
+    }
+
+    /* the rook of `side` on `square` leaves its home square */
+    fn clear_rook_castle_right(&mut self, side: ChessColor, square: usize) {
+        let home = if side == ChessColor::Wh {0} else {56};
+        if square == home + self.rook_file_q {
+            self.can_castle_q[side.index()] = false;
+        } else if square == home + self.rook_file_k {
+            self.can_castle_k[side.index()] = false;
+        }
+    }
+
+    /* the file of the rook castling with `mv` */
+    fn rook_file(&self, mv: &ChessMove) -> usize {
+        return if mv.castles_queenside() {self.rook_file_q} else {self.rook_file_k};
+    }
+
+    /* where the king and rook of `side` castling on the queens or kings side
+     * are and where they end up, as `(king, rook, king target, rook
+     * target)`. [None] unless the king is on its back rank with the rook on
+     * its castling file on the correct side of it */
+    fn castle_squares(&self, side: ChessColor, queens: bool)
+                      -> Option<(usize, usize, usize, usize)> {
+        let home = if side == ChessColor::Wh {0} else {56};
+        let king = self.king_position(side)?;
+        let rook = home + if queens {self.rook_file_q} else {self.rook_file_k};
+        if king / 8 != home / 8 || self.board[rook] != ChessPiece::R(side)
+            || (rook < king) != queens {
+            return None;
+        }
+        return Some((king, rook, home + if queens {2} else {6}, home + if queens {3} else {5}));
+    }
 
-        /* update possible moves for next turn */
-        self.next_moves[ChessColor::Wh as usize]
-            = self.find_legal_moves(&ChessColor::Wh);
-        self.next_moves[ChessColor::Bl as usize]
-            = self.find_legal_moves(&ChessColor::Bl);
-
-        /* check caste eligibility for next turn */
-        self.can_castle_now_q[ChessColor::Wh as usize]
-            = self.board[1] == ChessPiece::None
-            && self.board[2] == ChessPiece::None
-            && self.board[3] == ChessPiece::None;
-        self.can_castle_now_k[ChessColor::Wh as usize]
-            = self.board[5] == ChessPiece::None
-            && self.board[6] == ChessPiece::None;
-        self.can_castle_now_q[ChessColor::Bl as usize]
-            = self.board[57] == ChessPiece::None
-            && self.board[58] == ChessPiece::None
-            && self.board[59] == ChessPiece::None;
-        self.can_castle_now_k[ChessColor::Bl as usize]
-            = self.board[61] == ChessPiece::None
-            && self.board[62] == ChessPiece::None;
-
-        for mv in self.next_moves[ChessColor::Wh as usize].iter() {
-            if mv.target == 58 || mv.target == 59 || mv.target == 60 {
-                self.can_castle_now_q[ChessColor::Bl as usize] = false;
+    /* recompute the cached moves and state from the board, castle rights and
+     * en passant squares */
+    fn update_state(&mut self) {
+        /* Update possible moves for next turn. No other move depends on
+         * castle eligibility, so every move except castling is found first
+         * and the castle moves are added once eligibility is known. */
+        self.update_pseudo_moves();
+        self.next_moves[ChessColor::Wh.index()]
+            = self.find_legal_piece_moves(&ChessColor::Wh);
+        self.next_moves[ChessColor::Bl.index()]
+            = self.find_legal_piece_moves(&ChessColor::Bl);
+
+        /* check caste eligibility for next turn: everything between the
+         * king, the rook and their targets has to be empty, and the other
+         * side can't attack the king's square, its target or any square in
+         * between, so the king can't castle out of, through or into check */
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            for queens in [false, true] {
+                let now = match self.castle_squares(side, queens) {
+                    Some((king, rook, king_target, rook_target)) => {
+                        let lo = king.min(rook).min(king_target).min(rook_target);
+                        let hi = king.max(rook).max(king_target).max(rook_target);
+                        let blocked = (lo..=hi).filter(|i| *i != king && *i != rook)
+                                               .any(|i| self.board[i] != ChessPiece::None);
+                        let attacked = (king.min(king_target)..=king.max(king_target))
+                            .any(|i| self.is_square_attacked(i, side.opposite()));
+                        !blocked && !attacked
+                    },
+                    _ => false,
+                };
+                if queens {
+                    self.can_castle_now_q[side.index()] = now;
+                } else {
+                    self.can_castle_now_k[side.index()] = now;
+                }
             }
-            if mv.target == 60 || mv.target == 61 || mv.target == 62 {
-                self.can_castle_now_k[ChessColor::Bl as usize] = false;
+        }
+
+        /* add castle moves now that castle eligibility is known */
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            let castles = self.find_legal_castle_moves(&side);
+            self.next_moves[side.index()].extend(castles);
+        }
+
+        self.update_status();
+
+        #[cfg(debug_assertions)]
+        self.check_invariants();
+    }
+
+    /* whether the side to move is in check and whether it can move */
+    fn update_status(&mut self) {
+        let side = self.side_to_move();
+        self.state = match (self.is_check_for(side), self.has_legal_move(side)) {
+            (true, true) => ChessState::Check,
+            (true, false) => ChessState::Checkmate,
+            (false, true) => ChessState::Normal,
+            (false, false) => ChessState::Stalemate,
+        };
+    }
+
+    /* regenerates the pseudo-legal moves of both sides from scratch */
+    fn regenerate_pseudo_moves(&mut self) {
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            self.pseudo_moves[side.index()] = self.find_piece_moves(&side);
+        }
+        self.pseudo_board = self.board;
+        self.pseudo_en_passant = self.en_passant_loc;
+    }
+
+    /* Brings `pseudo_moves` up to date with the board and en passant squares.
+     *
+     * The pseudo-legal moves of a piece only depend on its own square, the
+     * squares it can move to or slide through (up to and including the first
+     * piece in the way) and, for pawns, the en passant squares. So a piece
+     * keeps its cached moves unless one of those squares changed occupancy
+     * since they were found, or it's a pawn next to an old or new en passant
+     * square. Every other piece is regenerated, and the moves stay ordered by
+     * origin square. The cache is then exactly what `find_piece_moves` gives
+     * for the current board, in the same order, which `check_invariants`
+     * asserts in debug builds. */
+    fn update_pseudo_moves(&mut self) {
+        let changed: Vec<usize> = (0..64).filter(|i| self.board[*i] != self.pseudo_board[*i])
+                                         .collect();
+        /* a move changes at most four squares, anything more is a new board */
+        if changed.len() > 4 {
+            self.regenerate_pseudo_moves();
+            return;
+        }
+
+        let mut stale = [false; 64];
+        for square in changed {
+            self.mark_pieces_reaching(square, &mut stale);
+        }
+        if self.en_passant_loc != self.pseudo_en_passant {
+            for (origin, _) in self.en_passant_loc.iter().chain(&self.pseudo_en_passant).flatten() {
+                stale[*origin] = true;
             }
         }
-        for mv in self.next_moves[ChessColor::Bl as usize].iter() {
-            if mv.target == 2 || mv.target == 3 || mv.target == 4 {
-                self.can_castle_now_q[ChessColor::Wh as usize] = false;
+
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            let mut fresh = Vec::new();
+            for i in (0..64).filter(|i| stale[*i] && self.board[*i].color() == Some(side)) {
+                self.piece_moves(i, &mut fresh, &side);
             }
-            if mv.target == 4 || mv.target == 5 || mv.target == 6 {
-                self.can_castle_now_k[ChessColor::Wh as usize] = false;
+
+            /* both lists are ordered by origin and never share one */
+            let cached = std::mem::take(&mut self.pseudo_moves[side.index()]);
+            let mut merged = Vec::with_capacity(MOVE_LIST_CAPACITY);
+            let mut fresh = fresh.into_iter().peekable();
+            for mv in cached.into_iter().filter(|mv| !stale[mv.origin]) {
+                while let Some(new_mv) = fresh.next_if(|new_mv| new_mv.origin < mv.origin) {
+                    merged.push(new_mv);
+                }
+                merged.push(mv);
             }
+            merged.extend(fresh);
+            self.pseudo_moves[side.index()] = merged;
         }
+        self.pseudo_board = self.board;
+        self.pseudo_en_passant = self.en_passant_loc;
+    }
 
-        /* update possible moves again since
-         * castle eligibility may have changed */
-        self.next_moves[ChessColor::Wh as usize]
-            = self.find_legal_moves(&ChessColor::Wh);
-        self.next_moves[ChessColor::Bl as usize]
-            = self.find_legal_moves(&ChessColor::Bl);
+    /* marks `square` and every square holding a piece whose pseudo-legal
+     * moves look at `square` in `stale` */
+    fn mark_pieces_reaching(&self, square: usize, stale: &mut [bool; 64]) {
+        use ChessPiece::*;
 
-        /* TODO: place in move generation and save as "next state?"
-         * Would be useful for algebraic notation. */
-        if self.next_moves[self.turn as usize].iter().any(|x| x.captures == ChessPiece::K(self.turn.opposite())) {
-            self.state = ChessState::Check;
-        } else {
-            self.state = ChessState::Normal;
+        stale[square] = true;
+        /* kings and pawns step onto, or capture on, neighboring squares */
+        for (dx, dy) in QUEEN_DIRECTIONS {
+            if let Some(t) = neighbor(square, dx, dy) {
+                if matches!(self.board[t], K(_) | P(_)) {
+                    stale[t] = true;
+                }
+            }
+        }
+        /* a pawn's double step passes over the square in between */
+        for dy in [-2, 2] {
+            if let Some(t) = neighbor(square, 0, dy) {
+                if matches!(self.board[t], P(_)) {
+                    stale[t] = true;
+                }
+            }
+        }
+        for (dx, dy) in KNIGHT_OFFSETS {
+            if let Some(t) = neighbor(square, dx, dy) {
+                if matches!(self.board[t], N(_)) {
+                    stale[t] = true;
+                }
+            }
+        }
+        /* the first piece along each line, if it slides back along it */
+        for (dx, dy) in QUEEN_DIRECTIONS {
+            for j in 1..8 {
+                match neighbor(square, j*dx, j*dy) {
+                    Some(t) => if self.board[t] != None {
+                        let piece = self.board[t];
+                        if piece.is_slider() && piece.directions().contains(&(-dx, -dy)) {
+                            stale[t] = true;
+                        }
+                        break;
+                    },
+                    _ => break,
+                }
+            }
         }
+    }
 
-        return true;
+    /* Safety net for the move caches in debug builds: regenerates the legal
+     * moves of both sides the simple way, without the double check shortcut,
+     * the incrementally updated pseudo-legal moves or reusing the first pass
+     * of `update_state`, and compares them with the cached ones. Also makes
+     * sure neither side has more than one king, custom boards are allowed
+     * to have none. */
+    #[cfg(debug_assertions)]
+    fn check_invariants(&self) {
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            let kings = self.board.iter().filter(|x| **x == ChessPiece::K(side)).count();
+            assert!(kings <= 1, "{side:?} has {kings} kings");
+            assert_eq!(self.pseudo_moves[side.index()], self.find_piece_moves(&side),
+                       "cached pseudo-legal moves of {side:?} are out of date");
+
+            let mut fresh: Vec<ChessMove> = self.find_piece_moves(&side)
+                .into_iter()
+                .chain(self.find_castle_moves(&side))
+                .filter(|mv| self.is_move_legal(&side, mv))
+                .collect();
+            let mut cached = self.next_moves[side.index()].clone();
+            fresh.sort();
+            cached.sort();
+            assert_eq!(cached, fresh, "cached moves of {side:?} are out of date");
+        }
     }
 
     fn mv_promotion(&self, mv: ChessMove) -> Vec<ChessMove> {
@@ -439,7 +1446,7 @@ impl ChessGame {
             _ => return Vec::new(),
         };
 
-        if mv.target > 55 || mv.target < 8 {
+        if col.is_promotion_square(mv.target) {
             use ChessPiece::*;
 
             let mut out: Vec<ChessMove> = Vec::new();
@@ -458,58 +1465,128 @@ impl ChessGame {
         return self.board[origin].captures(origin, target, self.board[target]);
     }
 
-    fn mv_en_passant(&self, origin: usize, target: usize) -> ChessMove {
+    /**
+     * Builds an en passant capture by the pawn on `origin` moving to
+     * `target`. Fails with [MoveError::OutOfBounds] if either square is off
+     * the board, and with [MoveError::IllegalMove] unless `origin` holds a
+     * pawn and `target` is diagonally in front of it on rank 3 (for black) or
+     * rank 6 (for white). The move is not checked against the current en
+     * passant square, [ChessGame::make_move] does that.
+     */
+    pub fn mv_en_passant(&self, origin: usize, target: usize) -> Result<ChessMove, MoveError> {
+        if origin >= 64 || target >= 64 {
+            return Err(MoveError::OutOfBounds);
+        }
+
         let piece = self.board[origin];
-        let mut mv = ChessMove::to(piece, origin, target);
-        mv.captures = match piece.color() {
-            Some(col) => ChessPiece::P(col.opposite()),
-            _ => ChessPiece::None,
+        let col = match piece {
+            ChessPiece::P(col) => col,
+            _ => return Err(MoveError::IllegalMove),
         };
+        let rank = if col == ChessColor::Wh {5} else {2};
+        if target / 8 != rank
+            || neighbor(origin, -1, col.dir()) != Some(target)
+                && neighbor(origin, 1, col.dir()) != Some(target) {
+            return Err(MoveError::IllegalMove);
+        }
+
+        let mut mv = ChessMove::to(piece, origin, target);
+        mv.captures = ChessPiece::P(col.opposite());
         mv.en_passant = true;
 
-        return mv;
+        return Ok(mv);
     }
 
     fn mv_castle(&self, side: &ChessColor, queens: bool) -> ChessMove {
         use ChessPiece::*;
         use ChessColor::*;
 
-        let king = if *side == Wh {4} else {60};
-        let mut mv = ChessMove::to(K(*side), king, if queens {king - 2} else {king + 2});
+        let home = if *side == Wh {0} else {56};
+        let king = self.king_position(*side).unwrap_or(home + 4);
+        let mut mv = ChessMove::to(K(*side), king, home + if queens {2} else {6});
         mv.castles = true;
         return mv;
     }
 
-    fn apply_temp_move(&mut self, mv: &ChessMove) {
-        self.temp_board = self.board;
-        self.apply_move_internal(mv, false);
+    /**
+     * Returns the castle move of `color` on the kingside or queenside if it
+     * is currently legal, otherwise [None].
+     */
+    pub fn castling_move(&self, color: ChessColor, kingside: bool) -> Option<ChessMove> {
+        let mv = self.mv_castle(&color, !kingside);
+        return self.next_moves[color.index()].contains(&mv).then_some(mv);
     }
 
-    fn restore_temp_move(&mut self) {
-        self.board = self.temp_board;
+    fn position(&self) -> Position {
+        return Position {
+            board: self.board,
+            can_castle_k: self.can_castle_k,
+            can_castle_q: self.can_castle_q,
+            en_passant_loc: self.en_passant_loc,
+            turn: self.side_to_move(),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        };
     }
 
-    fn step(&self, i: usize, dx: isize, dy: isize, side: &ChessColor) -> Option<usize> {
-        let rdy: isize = dy * side.dir();
-        let x = (i % 8) as isize + dx;
-        let y = (i / 8) as isize + rdy;
+    /* restores `position` without touching the history */
+    fn restore(&mut self, position: &Position) {
+        self.board = position.board;
+        self.can_castle_k = position.can_castle_k;
+        self.can_castle_q = position.can_castle_q;
+        self.en_passant_loc = position.en_passant_loc;
+        self.turn = position.turn;
+        self.halfmove_clock = position.halfmove_clock;
+        self.fullmove_number = position.fullmove_number;
+        self.update_state_to_move();
+    }
 
-        return if 0 <= x && x <= 7 && 0 <= y && y <= 7 {
-            Some((8*y+x) as usize)
-        } else {
-            None
-        };
+    /* like `update_state` but for positions where `self.turn` is the side to
+     * move, rather than the side that just moved as after `apply_move` */
+    fn update_state_to_move(&mut self) {
+        self.moved_unswitched = false;
+        self.update_state();
     }
 
-    fn step_real(&self, i: usize, dx: isize, dy: isize) -> Option<usize> {
-        let x = (i % 8) as isize + dx;
-        let y = (i / 8) as isize + dy;
+    /* the square a pawn can be captured on en passant, if any */
+    fn en_passant_target(&self) -> Option<usize> {
+        /* both entries share the same target square */
+        return self.en_passant_loc.iter().flatten().next().map(|(_, t)| *t);
+    }
 
-        return if 0 <= x && x <= 7 && 0 <= y && y <= 7 {
-            Some((8*y+x) as usize)
-        } else {
-            None
-        };
+    /* sets up en passant as if a pawn had just passed over `target` */
+    fn set_en_passant_target(&mut self, target: Option<usize>) {
+        self.en_passant_loc = [None; 2];
+        if let Some(target) = target {
+            let pawn = if target < 32 { target + 8 } else { target - 8 };
+            self.en_passant_loc[0] = neighbor(pawn, 1, 0).map(|loc| (loc, target));
+            self.en_passant_loc[1] = neighbor(pawn, -1, 0).map(|loc| (loc, target));
+        }
+    }
+
+    /**
+     * Returns the en passant target square after a pawn of `color` moved two
+     * squares forward to `last_double_push_target`, i.e. the square it
+     * passed over. For example a white pawn pushed to e4 gives e3, and a
+     * black pawn pushed to e5 gives e6. Returns [None] if either square is
+     * off the board.
+     */
+    pub fn en_passant_square_for(last_double_push_target: usize, color: ChessColor)
+                                 -> Option<usize> {
+        if last_double_push_target >= 64 {
+            return None;
+        }
+        return last_double_push_target.checked_add_signed(-8 * color.dir())
+                                      .filter(|square| *square < 64);
+    }
+
+    /* like `neighbor` but with `dy` relative to the direction `side` moves */
+    fn step(&self, i: usize, dx: isize, dy: isize, side: &ChessColor) -> Option<usize> {
+        return neighbor(i, dx, dy * side.dir());
+    }
+
+    fn step_real(&self, i: usize, dx: isize, dy: isize) -> Option<usize> {
+        return neighbor(i, dx, dy);
     }
 
     fn collides(&self, i: usize) -> bool {
@@ -534,7 +1611,7 @@ impl ChessGame {
 
         if !collides {
             out.extend(self.mv_promotion(piece.to(i, pos)));
-            if (i < 16 && i > 7) || (i < 56 && i > 47) {
+            if side.is_pawn_start_square(i) {
                 match self.step(i, 0, 2, side) {
                     Some(t) => if !self.collides(t) {
                         /* NOTE: can never be a promotion */
@@ -560,71 +1637,36 @@ impl ChessGame {
             match loc {
                 Some((origin, target)) => if i == origin {
                     /* NOTE: can never be a promotion */
-                    out.push(self.mv_en_passant(origin, target))
+                    out.extend(self.mv_en_passant(origin, target))
                 },
                 _ => (),
             }
         }
     }
 
-    fn rook_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
+    fn slide_moves(&self, i: usize, dirs: &[(isize, isize)], out: &mut Vec<ChessMove>, side: &ChessColor) {
         let piece = &self.board[i];
 
-        for j in 1..8 {
-            match self.step_real(i, 0, j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
-
-        for j in 1..8 {
-            match self.step_real(i, j, 0) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
-
-        for j in 1..8 {
-            match self.step_real(i, 0, -j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
+        for (dx, dy) in dirs {
+            /* at most 7 steps fit on the board */
+            for j in 1..8 {
+                match self.step_real(i, j*dx, j*dy) {
+                    Some(t) => if self.collides_opponent(t, side) {
+                        out.push(self.mv_captures(i, t));
+                        break;
+                    } else if !self.collides(t) {
+                        out.push(piece.to(i, t));
+                    } else {
+                        break;
+                    },
+                    _ => break,
+                }
             }
         }
+    }
 
-        for j in 1..8 {
-            match self.step_real(i, -j, 0) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
+    fn rook_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
+        self.slide_moves(i, &ROOK_DIRECTIONS, out, side);
     }
 
     fn knight_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
@@ -654,68 +1696,11 @@ impl ChessGame {
     }
 
     fn bishop_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
-        let piece = &self.board[i];
-
-        for j in 1..8 {
-            match self.step_real(i, j, j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
-
-        for j in 1..8 {
-            match self.step_real(i, -j, j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
-
-        for j in 1..8 {
-            match self.step_real(i, j, -j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
-
-        for j in 1..8 {
-            match self.step_real(i, -j, -j) {
-                Some(t) => if self.collides_opponent(t, side) {
-                    out.push(self.mv_captures(i, t));
-                    break;
-                } else if !self.collides(t) {
-                    out.push(piece.to(i, t));
-                } else {
-                    break;
-                },
-                _ => break,
-            }
-        }
+        self.slide_moves(i, &BISHOP_DIRECTIONS, out, side);
     }
 
     fn queen_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
-        self.rook_moves(i, out, side);
-        self.bishop_moves(i, out, side);
+        self.slide_moves(i, &QUEEN_DIRECTIONS, out, side);
     }
 
     fn king_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
@@ -744,555 +1729,2502 @@ impl ChessGame {
         }
     }
 
-    fn find_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
-        use ChessPiece::*;
-        let mut out: Vec<ChessMove> = Vec::new();
-        let it = self.board.iter()
-                           .enumerate()
-                           .filter(|(_, x)| **x != None
-                                   && x.color().unwrap() == *side);
-        for (i, piece) in it {
-            match piece {
-                P(_) => self.pawn_moves(i, &mut out, side),
-                R(_) => self.rook_moves(i, &mut out, side),
-                N(_) => self.knight_moves(i, &mut out, side),
-                B(_) => self.bishop_moves(i, &mut out, side),
-                Q(_) => self.queen_moves(i, &mut out, side),
-                K(_) => self.king_moves(i, &mut out, side),
-                _ => unreachable!(),
-            };
+    /* the pseudo-legal moves of the piece of `side` on `i` */
+    fn piece_moves(&self, i: usize, out: &mut Vec<ChessMove>, side: &ChessColor) {
+        use PieceKind::*;
+        match self.board[i].kind().unwrap() {
+            Pawn => self.pawn_moves(i, out, side),
+            Rook => self.rook_moves(i, out, side),
+            Knight => self.knight_moves(i, out, side),
+            Bishop => self.bishop_moves(i, out, side),
+            Queen => self.queen_moves(i, out, side),
+            King => self.king_moves(i, out, side),
+        };
+    }
+
+    fn find_piece_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
+        let mut out: Vec<ChessMove> = Vec::with_capacity(MOVE_LIST_CAPACITY);
+        for i in (0..64).filter(|i| self.board[*i].color() == Some(*side)) {
+            self.piece_moves(i, &mut out, side);
         }
 
-        if self.can_castle_k[*side as usize] && self.can_castle_now_k[*side as usize] {
+        return out;
+    }
+
+    fn find_castle_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
+        let mut out = Vec::new();
+        if self.can_castle_k[side.index()] && self.can_castle_now_k[side.index()] {
             out.push(self.mv_castle(side, false));
         }
-        if self.can_castle_q[*side as usize] && self.can_castle_now_q[*side as usize] {
+        if self.can_castle_q[side.index()] && self.can_castle_now_q[side.index()] {
             out.push(self.mv_castle(side, true));
         }
-
         return out;
     }
 
-    fn is_move_legal(&mut self, side: &ChessColor, mv: &ChessMove) -> bool {
-        self.apply_temp_move(&mv);
-        let result = self.find_moves(&side.opposite())
-                         .iter().all(|x| x.captures
-                                     != ChessPiece::K(*side));
-        self.restore_temp_move();
-        return result;
+    /* a move is legal if it doesn't leave the own king attacked, which is
+     * exactly when none of the opponent's pseudo-legal moves could capture
+     * it */
+    fn is_move_legal(&self, side: &ChessColor, mv: &ChessMove) -> bool {
+        let mut board = self.board;
+        apply_board_effects(&mut board, mv, self.rook_file(mv));
+        return match board.iter().position(|x| *x == ChessPiece::K(*side)) {
+            Some(king) => !square_attacked(&board, king, side.opposite()),
+            _ => true,
+        };
     }
 
-    fn find_legal_moves(&mut self, side: &ChessColor) -> Vec<ChessMove> {
-        return self.find_moves(side)
+    /* the legal moves of `side` except castling, out of the cached
+     * pseudo-legal ones */
+    fn find_legal_piece_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
+        /* in double check only the king can move */
+        let double_check = self.checkers(*side).len() > 1;
+        return self.pseudo_moves[side.index()]
                    .iter()
+                   .filter(|mv| !double_check || mv.piece == ChessPiece::K(*side))
                    .filter(|mv| self.is_move_legal(side, mv))
                    .copied()
                    .collect();
     }
 
+    fn find_legal_castle_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
+        return self.find_castle_moves(side)
+                   .into_iter()
+                   .filter(|mv| self.is_move_legal(side, mv))
+                   .collect();
+    }
+
+
     /**
      * Gets all legal moves for one `side`. This does not have to be the side
      * whose turn it is right now.
      *
-     * Will usually be called like this: `game.get_legal_moves(&game.turn);`
+     * Will usually be called like this: `game.get_legal_moves(&game.turn());`
      */
     pub fn get_legal_moves(&self, side: &ChessColor) -> Vec<ChessMove> {
         /* I hate the fact that I have to clone here becase the borrow checker
          * got angry with me */
-        return self.next_moves[*side as usize].clone();
+        return self.next_moves[side.index()].clone();
     }
 
     /**
-     * Returns [true] if the game is over.
+     * Gets the legal moves of both sides at once, indexed by
+     * [ChessColor::index], without copying them. The moves of the side whose
+     * turn it isn't are the moves it would have if it were its turn, e.g.
+     * for looking at threats.
      */
-    pub fn is_ended(&self) -> bool {
-        return self.next_moves[self.turn as usize].is_empty();
+    pub fn all_legal_moves(&self) -> &[Vec<ChessMove>; 2] {
+        return &self.next_moves;
     }
 
     /**
-     * Returns [true] if the current side is in check.
+     * Gets all legal moves for one `side` like [ChessGame::get_legal_moves],
+     * but sorted by origin square, then target square, then promotion piece
+     * (see the [Ord] implementation of [ChessMove]).
      */
-    pub fn is_check(&self) -> bool {
-        return self.state == ChessState::Check;
+    pub fn legal_moves_sorted(&self, side: ChessColor) -> Vec<ChessMove> {
+        let mut moves = self.get_legal_moves(&side);
+        moves.sort();
+        return moves;
     }
 
     /**
-     * Returns [true] if the game is over in checkmate.
+     * Gets all legal moves for one `side` like [ChessGame::get_legal_moves],
+     * leaving out any that are in `excluded`. Useful for searching the
+     * position again without the best moves found so far.
      */
-    pub fn is_checkmate(&self) -> bool {
-        return self.is_ended() && self.is_check();
+    pub fn legal_moves_except(&self, side: ChessColor, excluded: &[ChessMove]) -> Vec<ChessMove> {
+        return self.next_moves[side.index()]
+                   .iter()
+                   .filter(|mv| !excluded.contains(mv))
+                   .copied()
+                   .collect();
     }
 
     /**
-     * Returns [true] if the game is over in stalemate.
+     * Returns a copy of the game with the board mirrored vertically and the
+     * colors of all pieces swapped, so that white becomes black and the other
+     * way around. Castling rights, en passant and the side to move follow
+     * along. The move history is not kept.
+     *
+     * Any position and its flipped version are equally good for the side to
+     * move, which makes this useful for testing.
      */
-    pub fn is_stalemate(&self) -> bool {
-        return self.is_ended() && !self.is_check();
+    pub fn flip(&self) -> ChessGame {
+        let mut game = self.clone();
+        for i in 0..64 {
+            game.board[i ^ 56] = self.board[i].flip_color();
+        }
+        game.can_castle_k.reverse();
+        game.can_castle_q.reverse();
+        game.set_en_passant_target(self.en_passant_target().map(|t| t ^ 56));
+        game.turn = self.side_to_move().opposite();
+        game.history.clear();
+        game.update_state_to_move();
+
+        return game;
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
+    /**
+     * Mirrors a move the same way [ChessGame::flip] mirrors the board, so the
+     * legal moves of a flipped position are exactly the mirrored legal moves
+     * of the original one.
+     */
+    pub fn mirror_move(mv: &ChessMove) -> ChessMove {
+        return ChessMove {
+            piece: mv.piece.flip_color(),
+            origin: mv.origin ^ 56,
+            target: mv.target ^ 56,
+            captures: mv.captures.flip_color(),
+            promotes: mv.promotes.flip_color(),
+            ..*mv
+        };
+    }
 
-    use super::*;
+    /**
+     * Returns every move played with [ChessGame::apply_move] since the game
+     * was created or the board was last loaded, oldest first.
+     */
+    pub fn move_history(&self) -> Vec<ChessMove> {
+        return self.history.iter().map(|entry| entry.mv).collect();
+    }
+
+    /**
+     * Returns a copy of the game as it was after the first `n` half-moves of
+     * [ChessGame::move_history], i.e. the position the game started from for
+     * `n == 0`. `n` is clamped to the length of the history. The history is
+     * cut at the same point, so the copy can be played on from there.
+     *
+     * The positions come from the history itself, so this also works for
+     * games that started from a loaded board.
+     */
+    pub fn at_ply(&self, n: usize) -> ChessGame {
+        let mut game = self.clone();
+        if let Some(entry) = self.history.get(n) {
+            game.restore(&entry.position);
+            game.history.truncate(n);
+        }
+        return game;
+    }
+
+    /**
+     * Takes back the last move of [ChessGame::move_history], restoring the
+     * position before it exactly, including the turn, castle rights, en
+     * passant and clocks. Returns the move that was taken back, or [None] if
+     * there is nothing to undo.
+     */
+    pub fn undo_move(&mut self) -> Option<ChessMove> {
+        let entry = self.history.pop()?;
+        self.restore(&entry.position);
+        return Some(entry.mv);
+    }
+
+    /**
+     * Gets the legal moves of `side` made by pieces of kind `kind`. Castling
+     * counts as a king move.
+     */
+    pub fn legal_moves_of_type(&self, side: ChessColor, kind: PieceKind) -> Vec<ChessMove> {
+        return self.next_moves[side.index()]
+                   .iter()
+                   .filter(|mv| mv.piece.kind() == Some(kind))
+                   .copied()
+                   .collect();
+    }
+
+    /**
+     * Gets the legal moves of the piece on square `index` for the side to
+     * move (see [ChessGame::side_to_move]). Empty if the square is empty,
+     * holds a piece of the other side or if the piece can't move.
+     */
+    pub fn legal_moves_from(&self, index: usize) -> Vec<ChessMove> {
+        return self.next_moves[self.side_to_move().index()]
+                   .iter()
+                   .filter(|mv| mv.origin == index)
+                   .copied()
+                   .collect();
+    }
+
+    /**
+     * Gets the legal moves to square `index` for the side to move, e.g. to
+     * find which pieces can capture on a square. Like
+     * [ChessGame::legal_moves_from] this is empty if no such move exists.
+     */
+    pub fn legal_moves_to(&self, index: usize) -> Vec<ChessMove> {
+        return self.next_moves[self.side_to_move().index()]
+                   .iter()
+                   .filter(|mv| mv.target == index)
+                   .copied()
+                   .collect();
+    }
+
+    /**
+     * Returns [true] if the piece on `square` has at least one legal move.
+     * Like [ChessGame::legal_moves_from] this only considers the side to
+     * move, so it is [false] for empty squares and the other side's pieces.
+     */
+    pub fn piece_has_moves(&self, square: usize) -> bool {
+        return self.next_moves[self.side_to_move().index()]
+                   .iter()
+                   .any(|mv| mv.origin == square);
+    }
+
+    /**
+     * Gets the legal promotion moves of the pawn on `origin` to `target`, one
+     * for each piece it can promote to. Like [ChessGame::legal_moves_from]
+     * this only considers the side to move. Empty if there is no such
+     * promotion.
+     */
+    pub fn promotion_choices(&self, origin: usize, target: usize) -> Vec<ChessMove> {
+        return self.next_moves[self.side_to_move().index()]
+                   .iter()
+                   .filter(|mv| mv.origin == origin
+                           && mv.target == target
+                           && mv.promotes != ChessPiece::None)
+                   .copied()
+                   .collect();
+    }
+
+    /**
+     * Returns the squares the piece on square `from` can legally move to as a
+     * bitmask, where bit `i` is set if square `i` is reachable. Promotions to
+     * different pieces share one bit. Like [ChessGame::legal_moves_from] this
+     * only considers the side to move, and is `0` when the piece can't
+     * move.
+     */
+    pub fn legal_destinations(&self, from: usize) -> u64 {
+        return self.next_moves[self.side_to_move().index()]
+                   .iter()
+                   .filter(|mv| mv.origin == from)
+                   .fold(0, |mask, mv| mask | 1 << mv.target);
+    }
+
+    /**
+     * Returns [true] if playing `mv` would leave the king of the moving side
+     * attacked, which makes an otherwise possible move illegal. The move is
+     * only played on a copy of the board and is not checked in any other
+     * way. Always [false] for moves without a moving piece.
+     */
+    pub fn leaves_king_in_check(&self, mv: &ChessMove) -> bool {
+        return match mv.piece.color() {
+            Some(col) => !self.is_move_legal(&col, mv),
+            _ => false,
+        };
+    }
+
+    /**
+     * Returns [true] if playing `mv` would attack the king of the other side,
+     * including discovered checks and checks by the rook when castling. Like
+     * [ChessGame::leaves_king_in_check] the move is only played on a copy of
+     * the board and not checked in any other way.
+     */
+    pub fn move_gives_check(&self, mv: &ChessMove) -> bool {
+        let col = match mv.piece.color() {
+            Some(col) => col,
+            _ => return false,
+        };
+        let mut board = self.board;
+        apply_board_effects(&mut board, mv, self.rook_file(mv));
+        return match board.iter().position(|x| *x == ChessPiece::K(col.opposite())) {
+            Some(king) => square_attacked(&board, king, col),
+            _ => false,
+        };
+    }
+
+    /**
+     * Returns [true] if `mv` is a quiet move, i.e. neither a capture, a
+     * promotion nor a check. A quiescence search only looks at the moves
+     * that aren't quiet.
+     */
+    pub fn is_quiet(&self, mv: &ChessMove) -> bool {
+        return mv.captures == ChessPiece::None
+            && mv.promotes == ChessPiece::None
+            && !self.move_gives_check(mv);
+    }
+
+    /**
+     * Returns [true] if `side` has at least one legal move. Legal moves are
+     * found once whenever the position changes, so this doesn't generate any
+     * moves and is cheaper than `!get_legal_moves(side).is_empty()`, which
+     * copies the list.
+     */
+    pub fn has_legal_move(&self, side: ChessColor) -> bool {
+        return !self.next_moves[side.index()].is_empty();
+    }
+
+    /**
+     * Returns [true] if the game is over because the side to move (see
+     * [ChessGame::side_to_move]) has no legal moves, because 50 moves have
+     * been played without a capture or pawn move (see
+     * [ChessGame::is_fifty_move_claimable]), or because neither side can
+     * checkmate (see [ChessGame::is_insufficient_material]).
+     */
+    pub fn is_ended(&self) -> bool {
+        return self.is_out_of_moves() || self.is_fifty_move_claimable()
+            || self.is_insufficient_material();
+    }
+
+    /* whether the side to move has no legal moves, i.e. is mated or
+     * stalemated */
+    fn is_out_of_moves(&self) -> bool {
+        return matches!(self.state, ChessState::Checkmate | ChessState::Stalemate);
+    }
+
+    /**
+     * Returns whether the side to move (see [ChessGame::side_to_move]) is in
+     * check, checkmated or stalemated. Draws by other rules are not part of
+     * the status, see [ChessGame::draw_reason].
+     */
+    pub fn status(&self) -> ChessState {
+        return self.state;
+    }
+
+    /**
+     * Returns [true] if the side to move (see [ChessGame::side_to_move]) is
+     * in check, including checkmate.
+     */
+    pub fn is_check(&self) -> bool {
+        return matches!(self.state, ChessState::Check | ChessState::Checkmate);
+    }
+
+    /**
+     * Returns [true] if the game is over in checkmate.
+     */
+    pub fn is_checkmate(&self) -> bool {
+        return self.state == ChessState::Checkmate;
+    }
+
+    /**
+     * Returns [true] if the game is over in stalemate.
+     */
+    pub fn is_stalemate(&self) -> bool {
+        return self.state == ChessState::Stalemate;
+    }
+
+    /**
+     * Gets every legal move of the side to move that checkmates right away.
+     */
+    pub fn mate_in_one_moves(&self) -> Vec<ChessMove> {
+        if self.moved_unswitched {
+            return self.with_side_to_move().mate_in_one_moves();
+        }
+        return self.next_moves[self.turn.index()]
+                   .iter()
+                   .filter(|mv| self.clone_with_move(mv).is_ok_and(|next| next.is_checkmate()))
+                   .copied()
+                   .collect();
+    }
+
+    /**
+     * Returns the outcome of the game for the side to move. Checkmate is a
+     * win for the other side, while stalemate, the fifty-move rule (see
+     * [ChessGame::is_fifty_move_claimable]) and insufficient material (see
+     * [ChessGame::is_insufficient_material]) are draws. This is
+     * [GameResult::Ongoing] exactly when [ChessGame::is_ended] is [false].
+     * Threefold repetition has to be claimed and doesn't end the game, see
+     * [ChessGame::draw_reason].
+     */
+    pub fn result(&self) -> GameResult {
+        if self.is_checkmate() {
+            return match self.side_to_move() {
+                ChessColor::Wh => GameResult::BlackWins,
+                ChessColor::Bl => GameResult::WhiteWins,
+            };
+        }
+        if self.is_stalemate() || self.is_fifty_move_claimable()
+            || self.is_insufficient_material() {
+            return GameResult::Draw;
+        }
+        return GameResult::Ongoing;
+    }
+
+    /**
+     * Returns the number of plies since the last capture or pawn move.
+     */
+    pub fn halfmove_clock(&self) -> u32 {
+        return self.halfmove_clock;
+    }
+
+    /**
+     * Returns [true] if a draw can be claimed under the fifty-move rule, i.e.
+     * if neither side has captured or moved a pawn in the last 50 moves.
+     *
+     * Note that the rule counts full moves (one by each side) while the clock
+     * counts plies, so this happens after 100 plies.
+     */
+    pub fn is_fifty_move_claimable(&self) -> bool {
+        return self.halfmove_clock >= 100;
+    }
+
+    /**
+     * Returns [true] if the game is automatically drawn under the
+     * seventy-five-move rule, i.e. after 150 plies without a capture or pawn
+     * move. Unlike [ChessGame::is_fifty_move_claimable] this does not need to
+     * be claimed by a player.
+     */
+    pub fn is_seventyfive_move_draw(&self) -> bool {
+        return self.halfmove_clock >= 150;
+    }
+
+    /**
+     * Returns the ply at which the last capture or pawn move was played, i.e.
+     * when the halfmove clock was last reset. Plies are counted from the
+     * start of the game using the fullmove number and the side to move (see
+     * [ChessGame::side_to_move]), so this is 0 if no capture or pawn move has
+     * been played yet.
+     */
+    pub fn last_irreversible_ply(&self) -> u32 {
+        let ply = 2*self.fullmove_number.saturating_sub(1) + self.side_to_move().index() as u32;
+        return ply.saturating_sub(self.halfmove_clock);
+    }
+
+    /**
+     * Returns the position of the king of color `color`, or [None] if there is
+     * no such king on the board. Custom boards without kings are allowed.
+     */
+    pub fn king_position(&self, color: ChessColor) -> Option<usize> {
+        return self.board.iter().position(|x| *x == ChessPiece::K(color));
+    }
+
+    /**
+     * Returns [true] if any piece of color `by` attacks `square`. Pieces
+     * attack squares occupied by both colors, and pawns only attack
+     * diagonally.
+     */
+    pub fn is_square_attacked(&self, square: usize, by: ChessColor) -> bool {
+        return square_attacked(&self.board, square, by);
+    }
+
+    /**
+     * Counts the pieces attacking `square` as `(defenders, attackers)`, where
+     * defenders belong to the side to move and attackers to the other side.
+     * Only direct attacks count, so a rook behind a queen on the same file
+     * is not included. A cheap hint of whether a square is safe to move to.
+     */
+    pub fn defenders_vs_attackers(&self, square: usize) -> (usize, usize) {
+        let mut defenders = 0;
+        let mut attackers = 0;
+        let side = self.side_to_move();
+        visit_square_attackers(&self.board, square, side, |_| defenders += 1);
+        visit_square_attackers(&self.board, square, side.opposite(), |_| attackers += 1);
+        return (defenders, attackers);
+    }
+
+    /**
+     * Returns the squares of every piece giving check to the king of color
+     * `color`, regardless of whose turn it is. Empty if the king isn't in
+     * check or if `color` has no king.
+     */
+    pub fn checkers(&self, color: ChessColor) -> Vec<usize> {
+        return match self.king_position(color) {
+            Some(king) => square_attackers(&self.board, king, color.opposite()),
+            _ => Vec::new(),
+        };
+    }
+
+    /**
+     * Returns the direction the piece on `square` is pinned along, or [None]
+     * if it isn't pinned to its own king or `square` is not on the board.
+     * The direction is a unit `(dx, dy)` step pointing from the king towards
+     * the pinning piece. A pinned piece can still move along this line, e.g.
+     * to capture the pinner.
+     */
+    pub fn pin_direction(&self, square: usize) -> Option<(isize, isize)> {
+        let col = self.board.get(square)?.color()?;
+        let king = self.king_position(col)?;
+        if king == square {
+            return None;
+        }
+
+        let dx = (square % 8) as isize - (king % 8) as isize;
+        let dy = (square / 8) as isize - (king / 8) as isize;
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return None;
+        }
+        let dir = (dx.signum(), dy.signum());
+
+        /* nothing between the king and the piece */
+        let mut pos = neighbor(king, dir.0, dir.1)?;
+        while pos != square {
+            if self.board[pos] != ChessPiece::None {
+                return None;
+            }
+            pos = neighbor(pos, dir.0, dir.1)?;
+        }
+
+        /* and an enemy slider moving along this line behind it */
+        while let Some(t) = neighbor(pos, dir.0, dir.1) {
+            pos = t;
+            let piece = self.board[pos];
+            if piece == ChessPiece::None {
+                continue;
+            }
+            return if piece.color() == Some(col.opposite())
+                      && piece.is_slider()
+                      && piece.directions().contains(&dir) {
+                Some(dir)
+            } else {
+                None
+            };
+        }
+        return None;
+    }
+
+    /**
+     * Returns [true] if the king of color `color` is attacked, regardless of
+     * whose turn it is. Unlike [ChessGame::is_check] this is computed from the
+     * board directly. Always [false] if `color` has no king.
+     */
+    pub fn is_check_for(&self, color: ChessColor) -> bool {
+        return match self.king_position(color) {
+            Some(king) => self.is_square_attacked(king, color.opposite()),
+            _ => false,
+        };
+    }
+
+    /**
+     * Counts every piece on the board per type and color, and computes the
+     * net material balance. See [MaterialDetail].
+     */
+    pub fn material_detail(&self) -> MaterialDetail {
+        use ChessPiece::*;
+
+        let mut detail = MaterialDetail::default();
+        for piece in self.board.iter() {
+            let col = match piece.color() {
+                Some(col) => col,
+                _ => continue,
+            };
+            let count = match piece {
+                P(_) => &mut detail.pawns,
+                R(_) => &mut detail.rooks,
+                N(_) => &mut detail.knights,
+                B(_) => &mut detail.bishops,
+                Q(_) => &mut detail.queens,
+                K(_) => &mut detail.kings,
+                None => unreachable!(),
+            };
+            count[col.index()] += 1;
+            detail.balance += piece.value() * col.dir() as i32;
+        }
+
+        return detail;
+    }
+
+    /**
+     * Counts how many of `piece` are on the board, e.g. `ChessPiece::N(Wh)`
+     * for white's knights.
+     */
+    pub fn piece_count(&self, piece: ChessPiece) -> usize {
+        return self.board.iter().filter(|x| **x == piece).count();
+    }
+
+    /**
+     * Adds up the material of `side` in pawns, where pawns are worth 1,
+     * knights and bishops 3, rooks 5 and queens 9. The king is not counted,
+     * so the starting position is worth 39 for each side.
+     */
+    pub fn material(&self, side: ChessColor) -> i32 {
+        return self.board.iter()
+                   .filter(|x| x.color() == Some(side))
+                   .map(|x| x.value() / 100)
+                   .sum();
+    }
+
+    /**
+     * The material of white minus the material of black in pawns, see
+     * [ChessGame::material]. [MaterialDetail::balance] is the same in
+     * centipawns.
+     */
+    pub fn material_balance(&self) -> i32 {
+        return self.material(ChessColor::Wh) - self.material(ChessColor::Bl);
+    }
+
+    /**
+     * Describes the material on the board as the pieces of white, a `v` and
+     * the pieces of black, each starting with the king and then ordered by
+     * value (queens, rooks, bishops, knights, pawns), e.g. `KQvKR` or
+     * `KRPPvKR`. Useful for telling which kind of endgame is on the board.
+     */
+    pub fn material_signature(&self) -> String {
+        use PieceKind::*;
+
+        let mut counts = [[0; 6]; 2];
+        for piece in self.board.iter() {
+            if let (Some(kind), Some(col)) = (piece.kind(), piece.color()) {
+                counts[col.index()][kind as usize] += 1;
+            }
+        }
+
+        let mut out = String::new();
+        for col in [ChessColor::Wh, ChessColor::Bl] {
+            if col == ChessColor::Bl {
+                out.push('v');
+            }
+            for kind in [King, Queen, Rook, Bishop, Knight, Pawn] {
+                let letter = kind.with_color(ChessColor::Wh).letter();
+                for _ in 0..counts[col.index()][kind as usize] {
+                    out.push(letter);
+                }
+            }
+        }
+        return out;
+    }
+}
+
+impl Default for ChessGame {
+    fn default() -> ChessGame {
+        return ChessGame::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn literally_redundant() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        assert_eq!(*game.get_board(), [
+            R(Wh), N(Wh), B(Wh), Q(Wh), K(Wh), B(Wh), N(Wh), R(Wh),
+            P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl),
+            R(Bl), N(Bl), B(Bl), Q(Bl), K(Bl), B(Bl), N(Bl), R(Bl),
+        ]);
+    }
+
+    /* checks the cached legal moves against the old way of finding them,
+     * playing every pseudo-legal move and looking for a reply taking the
+     * king */
+    fn check_legal_moves(game: &ChessGame, depth: u32) {
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            let mut pseudo = game.find_piece_moves(&side);
+            pseudo.extend(game.find_castle_moves(&side));
+            let expected: HashSet<ChessMove> = pseudo.into_iter().filter(|mv| {
+                let mut next = game.clone();
+                apply_board_effects(&mut next.board, mv, game.rook_file(mv));
+                return !next.find_piece_moves(&side.opposite())
+                            .iter()
+                            .any(|reply| next.board[reply.target] == ChessPiece::K(side));
+            }).collect();
+            let actual: HashSet<ChessMove> = game.get_legal_moves(&side).into_iter().collect();
+            assert_eq!(actual, expected);
+        }
+
+        if depth == 0 {
+            return;
+        }
+        for mv in game.get_legal_moves(&game.turn) {
+            let mut next = game.clone();
+            next.apply_move(&mv).unwrap();
+            next.switch_turn();
+            check_legal_moves(&next, depth - 1);
+        }
+    }
+
+    #[test]
+    fn legal_moves_reference() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        check_legal_moves(&ChessGame::new(), 2);
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None,  None,  None,  K(Wh), None,  None,  R(Wh),
+            P(Wh), None,  None,  None,  None,  P(Wh), None,  None,
+            None,  None,  N(Wh), None,  None,  None,  None,  None,
+            None,  B(Bl), None,  P(Wh), P(Bl), None,  None,  None,
+            None,  None,  None,  None,  None,  None,  None,  Q(Bl),
+            None,  None,  None,  None,  None,  None,  None,  None,
+            P(Bl), None,  None,  None,  None,  None,  None,  P(Bl),
+            R(Bl), None,  None,  None,  K(Bl), None,  None,  R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        check_legal_moves(&game, 2);
+    }
+
+    /* checks that the flipped game has exactly the mirrored legal moves */
+    fn check_mirrored_moves(game: &ChessGame, depth: u32) {
+        let flipped = game.flip();
+        for side in [ChessColor::Wh, ChessColor::Bl] {
+            let mirrored: HashSet<ChessMove> = game.get_legal_moves(&side)
+                .iter()
+                .map(ChessGame::mirror_move)
+                .collect();
+            let actual: HashSet<ChessMove> = flipped.get_legal_moves(&side.opposite())
+                .into_iter()
+                .collect();
+            assert_eq!(actual, mirrored);
+        }
+
+        if depth == 0 {
+            return;
+        }
+        for mv in game.get_legal_moves(&game.turn) {
+            let mut next = game.clone();
+            next.make_move(&mv).unwrap();
+            check_mirrored_moves(&next, depth - 1);
+        }
+    }
+
+    #[test]
+    fn mirror_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mv = ChessMove {
+            promotes: Q(Wh), ..ChessMove::captures(P(Wh), 52, 61, N(Bl))
+        };
+        assert_eq!(ChessGame::mirror_move(&mv), ChessMove {
+            promotes: Q(Bl), ..ChessMove::captures(P(Bl), 12, 5, N(Wh))
+        });
+
+        check_mirrored_moves(&ChessGame::new(), 2);
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None,  None,  None,  K(Wh), None,  None,  R(Wh),
+            P(Wh), None,  None,  None,  None,  P(Wh), None,  None,
+            None,  None,  N(Wh), None,  None,  None,  None,  None,
+            None,  B(Bl), None,  P(Wh), P(Bl), None,  None,  None,
+            None,  None,  None,  None,  None,  None,  None,  Q(Bl),
+            None,  None,  None,  None,  None,  None,  None,  None,
+            P(Bl), None,  None,  None,  None,  None,  None,  P(Bl),
+            R(Bl), None,  None,  None,  K(Bl), None,  None,  R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        check_mirrored_moves(&game, 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of date")]
+    fn check_invariants() {
+        let mut game = ChessGame::new();
+        game.check_invariants();
+
+        game.board[12] = ChessPiece::None;
+        game.check_invariants();
+    }
+
+    #[test]
+    fn force_apply() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+
+        /* a black knight jumping into white's camp on white's turn */
+        let mv = ChessMove::captures(N(Bl), 57, 10, P(Wh));
+        assert!(game.clone().make_move(&mv).is_err());
+        game.force_apply(&mv);
+        assert_eq!(game.board[57], None);
+        assert_eq!(game.board[10], N(Bl));
+        assert_eq!(game.turn(), Wh);
+        assert!(game.is_check_for(Wh));
+        assert_eq!(game.move_history(), [mv]);
+
+        /* castling straight through the pieces in the way */
+        game.force_apply(&ChessMove { castles: true, ..ChessMove::to(K(Bl), 60, 62) });
+        assert_eq!(game.board[62], K(Bl));
+        assert_eq!(game.board[61], R(Bl));
+        assert!(!game.can_castle_k[Bl.index()]);
+
+        game.load_board([None; 64]);
+        game.force_apply(&ChessMove { promotes: Q(Wh), ..ChessMove::to(P(Wh), 52, 60) });
+        assert_eq!(game.board[60], Q(Wh));
+        assert_eq!(game.get_legal_moves(&Wh).len(), 21);
+    }
+
+    #[test]
+    fn pawn_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None,  None, None,  None, None,  None,  None, None,
+            P(Wh), None, P(Wh), None, P(Wh), P(Wh), None, None,
+            None,  None, None,  None, P(Bl), None,  None, None,
+            P(Wh), None, None,  None, None,  None,  None, None,
+            None,  None, None,  None, None,  None,  None, None,
+            None,  None, P(Wh), None, None,  None,  None, None,
+            None,  None, None,  None, None,  None,  None, None,
+            None,  None, None,  None, None,  None,  None, None,
+        ]);
+
+        /* Make this not depend on order somehow */
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(P(Wh), 8, 16),
+            ChessMove::to(P(Wh), 10, 18),
+            ChessMove::to(P(Wh), 10, 26),
+            ChessMove::to(P(Wh), 13, 21),
+            ChessMove::to(P(Wh), 13, 29),
+            ChessMove::to(P(Wh), 24, 32),
+            ChessMove::captures(P(Wh), 13, 20, P(Bl)),
+            ChessMove::to(P(Wh), 42, 50),
+        ]));
+    }
+
+    #[test]
+    fn rook_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None,  None,  None, None, None,
+            None, None, None, None,  None,  None, None, None,
+            None, None, None, None,  None,  None, None, None,
+            None, None, None, R(Wh), P(Wh), None, None, None,
+            None, None, None, None,  None,  None, None, None,
+            None, None, None, None,  None,  None, None, None,
+            None, None, None, B(Bl), None,  None, None, None,
+            None, None, None, None,  None,  None, None, None,
+        ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(P(Wh), 28, 36),
+
+            ChessMove::to(R(Wh), 27, 19),
+            ChessMove::to(R(Wh), 27, 11),
+            ChessMove::to(R(Wh), 27, 3),
+
+            ChessMove::to(R(Wh), 27, 35),
+            ChessMove::to(R(Wh), 27, 43),
+            ChessMove::captures(R(Wh), 27, 51, B(Bl)),
+
+            ChessMove::to(R(Wh), 27, 26),
+            ChessMove::to(R(Wh), 27, 25),
+            ChessMove::to(R(Wh), 27, 24),
+        ]));
+    }
+
+    #[test]
+    fn slider_range() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            Q(Wh), None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+            None,  None, None, None, None, None, None, None,
+        ]);
+
+        /* the far corners are seven steps away */
+        let moves = game.get_legal_moves(&Wh);
+        assert_eq!(moves.len(), 21);
+        assert!(moves.contains(&ChessMove::to(Q(Wh), 0, 7)));
+        assert!(moves.contains(&ChessMove::to(Q(Wh), 0, 56)));
+        assert!(moves.contains(&ChessMove::to(Q(Wh), 0, 63)));
+    }
+
+    #[test]
+    fn knight_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  None,  None,  None, None, None,
+            None, None, N(Wh), None,  None,  None, None, None,
+            None, None, None,  None,  P(Wh), None, None, None,
+            None, None, None,  B(Bl), None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+        ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(N(Wh), 10, 0),
+            ChessMove::to(N(Wh), 10, 4),
+            ChessMove::to(N(Wh), 10, 16),
+            ChessMove::to(N(Wh), 10, 25),
+            ChessMove::captures(N(Wh), 10, 27, B(Bl)),
+
+            ChessMove::to(P(Wh), 20, 28),
+            ChessMove::captures(P(Wh), 20, 27, B(Bl)),
+        ]));
+    }
+
+    #[test]
+    fn bishop_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, B(Wh), None,  None, None, None,
+            None,  None, None, None,  P(Wh), None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            B(Bl), None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+        ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(P(Wh), 36, 44),
+
+            ChessMove::to(B(Wh), 27, 18),
+            ChessMove::to(B(Wh), 27, 9),
+            ChessMove::to(B(Wh), 27, 0),
+
+            ChessMove::to(B(Wh), 27, 34),
+            ChessMove::to(B(Wh), 27, 41),
+            ChessMove::captures(B(Wh), 27, 48, B(Bl)),
+
+            ChessMove::to(B(Wh), 27, 20),
+            ChessMove::to(B(Wh), 27, 13),
+            ChessMove::to(B(Wh), 27, 6),
+        ]));
+    }
+
+    #[test]
+    fn queen_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None,  None, None, None,  None,  None, None,  None,
+            None,  None, None, None,  None,  None, None,  None,
+            None,  None, None, P(Wh), None,  None, None,  None,
+            None,  None, None, Q(Wh), None,  None, B(Bl), None,
+            None,  None, None, None,  P(Wh), None, None,  None,
+            None,  None, None, None,  None,  None, None,  None,
+            B(Bl), None, None, None,  None,  None, None,  None,
+            None,  None, None, None,  None,  None, None,  None,
+        ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(P(Wh), 36, 44),
+
+            ChessMove::to(Q(Wh), 27, 18),
+            ChessMove::to(Q(Wh), 27, 9),
+            ChessMove::to(Q(Wh), 27, 0),
+
+            ChessMove::to(Q(Wh), 27, 34),
+            ChessMove::to(Q(Wh), 27, 41),
+            ChessMove::captures(Q(Wh), 27, 48, B(Bl)),
+
+            ChessMove::to(Q(Wh), 27, 20),
+            ChessMove::to(Q(Wh), 27, 13),
+            ChessMove::to(Q(Wh), 27, 6),
+
+            ChessMove::to(Q(Wh), 27, 26),
+            ChessMove::to(Q(Wh), 27, 25),
+            ChessMove::to(Q(Wh), 27, 24),
+
+            ChessMove::to(Q(Wh), 27, 28),
+            ChessMove::to(Q(Wh), 27, 29),
+            ChessMove::captures(Q(Wh), 27, 30, B(Bl)),
+
+            ChessMove::to(Q(Wh), 27, 35),
+            ChessMove::to(Q(Wh), 27, 43),
+            ChessMove::to(Q(Wh), 27, 51),
+            ChessMove::to(Q(Wh), 27, 59),
+        ]));
+    }
+
+    #[test]
+    fn king_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  None,  None, None, None, None,
+            None, None, None,  None,  None, None, None, None,
+            None, None, None,  P(Wh), None, None, None, None,
+            None, None, B(Bl), K(Wh), None, None, None, None,
+            None, None, None,  None,  None, None, None, None,
+            None, None, None,  None,  None, None, None, None,
+            None, None, None,  None,  None, None, None, None,
+            None, None, None,  None,  None, None, None, None,
+        ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        assert_eq!(moves, HashSet::from([
+            ChessMove::to(K(Wh), 27, 18),
+            ChessMove::to(K(Wh), 27, 20),
+            ChessMove::to(K(Wh), 27, 28),
+            ChessMove::to(K(Wh), 27, 36),
+            ChessMove::to(K(Wh), 27, 34),
+            ChessMove::captures(K(Wh), 27, 26, B(Bl)),
+
+            ChessMove::captures(P(Wh), 19, 26, B(Bl)),
+        ]));
+    }
+
+    #[test]
+    fn double_check() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  None,  K(Wh), None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  N(Bl), None,  None, None, None,
+            None, None, None,  None,  None,  None, None, Q(Wh),
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, K(Bl), None,  R(Bl), None, None, None,
+        ]);
+
+        let mut checkers = game.checkers(Wh);
+        checkers.sort();
+        assert_eq!(checkers, vec![19, 60]);
+        assert!(game.checkers(Bl).is_empty());
+
+        /* the queen could take the knight or block the rook if it weren't
+         * for the double check */
+        assert_eq!(game.legal_moves_sorted(Wh), vec![
+            ChessMove::to(K(Wh), 4, 3),
+            ChessMove::to(K(Wh), 4, 5),
+            ChessMove::to(K(Wh), 4, 11),
+        ]);
+    }
+
+    #[test]
+    fn pin_direction() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  None,  K(Wh), None,  None, None,
+            R(Wh), None, None, None,  N(Wh), None,  None, None,
+            None, None, B(Wh), None,  None,  None,  P(Wh), None,
+            None, None, None,  None,  None,  None,  None, B(Bl),
+            Q(Bl), None, None, None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  R(Bl), None,  None, None,
+            None, None, None,  None,  K(Bl), None,  None, None,
+        ]);
+
+        /* pinned along the file, the diagonal and the other diagonal */
+        assert_eq!(game.pin_direction(12), Some((0, 1)));
+        assert_eq!(game.pin_direction(18), Some((-1, 1)));
+        assert_eq!(game.pin_direction(22), Some((1, 1)));
+        /* not on a line with the king, the king itself and an enemy piece */
+        assert_eq!(game.pin_direction(8), Option::None);
+        assert_eq!(game.pin_direction(4), Option::None);
+        assert_eq!(game.pin_direction(40), Option::None);
+        assert_eq!(game.pin_direction(64), Option::None);
+
+        /* the pinned knight can't move, and the pinned bishop only along the
+         * diagonal */
+        assert!(game.legal_moves_from(12).is_empty());
+        let mut targets: Vec<usize> = game.legal_moves_from(18).iter().map(|mv| mv.target).collect();
+        targets.sort();
+        assert_eq!(targets, vec![11, 25, 32]);
+    }
+
+    #[test]
+    fn legal_moves_from_to() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.legal_moves_from(12),
+                   vec![ChessMove::to(P(Wh), 12, 20), ChessMove::to(P(Wh), 12, 28)]);
+        /* an empty square, a piece of the other side and a piece that can't
+         * move */
+        for square in [20, 52, 0] {
+            assert!(game.legal_moves_from(square).is_empty(), "{square}");
+        }
+
+        let mut to_f3 = game.legal_moves_to(21);
+        to_f3.sort();
+        assert_eq!(to_f3, vec![ChessMove::to(N(Wh), 6, 21), ChessMove::to(P(Wh), 13, 21)]);
+        assert!(game.legal_moves_to(44).is_empty());
+        assert!(game.legal_moves_to(8).is_empty());
+
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        assert_eq!(game.legal_moves_to(44), vec![ChessMove::to(P(Bl), 52, 44)]);
+        assert!(game.legal_moves_from(12).is_empty());
+    }
+
+    #[test]
+    fn leaves_king_in_check() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, B(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, R(Bl), None, None, K(Bl),
+        ]);
+
+        /* the bishop is pinned by the rook, the king may step aside */
+        assert!(game.leaves_king_in_check(&ChessMove::to(B(Wh), 12, 21)));
+        assert!(!game.leaves_king_in_check(&ChessMove::to(K(Wh), 4, 3)));
+        assert!(!game.leaves_king_in_check(&ChessMove::to(None, 0, 0)));
+    }
+
+    #[test]
+    fn checkmate() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  K(Bl), None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  R(Wh), None, None,
+            None, None, None,  None,  R(Wh), None,  None, None,
+            None, None, R(Wh), None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+        ]);
+        game.apply_move(&ChessMove::to(R(Wh), 21, 19)).unwrap();
+        game.switch_turn();
+
+        assert_eq!(game.status(), ChessState::Checkmate);
+        assert!(game.is_check());
+        let turn = game.turn;
+        assert_eq!(game.get_legal_moves(&turn), Vec::new());
+        assert!(!game.has_legal_move(turn));
+        assert!(game.has_legal_move(turn.opposite()));
+        assert!(game.is_checkmate());
+    }
+
+    #[test]
+    fn checkmate_before_switch() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        /* fool's mate, without switching the turn after the last move */
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 13, 21)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        game.make_move(&ChessMove::to(P(Wh), 14, 30)).unwrap();
+        game.apply_move(&ChessMove::to(Q(Bl), 59, 31)).unwrap();
+        assert_eq!(game.turn(), Bl);
+        assert_eq!(game.side_to_move(), Wh);
+        assert!(game.is_check());
+        assert!(game.is_ended());
+        assert!(game.is_checkmate());
+        assert!(!game.is_stalemate());
+        assert_eq!(game.result(), GameResult::BlackWins);
+
+        /* and the same after switching */
+        game.switch_turn();
+        assert_eq!(game.side_to_move(), Wh);
+        assert!(game.is_checkmate());
+        assert_eq!(game.result(), GameResult::BlackWins);
+
+        /* switching again without a move in between hands over the move */
+        game.switch_turn();
+        assert_eq!(game.side_to_move(), Bl);
+        assert!(!game.is_check());
+        assert!(!game.is_ended());
+
+        /* loaded boards are checked for the side whose turn it is */
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, R(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+        assert_eq!(game.side_to_move(), Bl);
+        assert!(game.is_check());
+    }
+
+    #[test]
+    fn queries_before_switch() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        /* black mates in one after white's last move, which is applied
+         * without switching the turn */
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 13, 21)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        game.apply_move(&ChessMove::to(P(Wh), 14, 30)).unwrap();
+        assert_eq!(game.side_to_move(), Bl);
+        let mate = ChessMove::to(Q(Bl), 59, 31);
+        let fen = game.to_fen();
+
+        assert_eq!(game.mate_in_one_moves(), vec![mate]);
+        assert_eq!(search::best_move(&game, 2), Some(mate));
+        assert_eq!(game.legal_moves_from(59).len(), 4);
+        assert!(game.legal_moves_from(12).is_empty());
+        assert!(game.legal_moves_to(31).contains(&mate));
+        assert!(game.piece_has_moves(59) && !game.piece_has_moves(11));
+        assert_eq!(game.legal_destinations(59) & 1 << 31, 1 << 31);
+        assert_eq!(game.parse_san("Qh4#"), Ok(mate));
+        assert_eq!(game.random_move(3).unwrap().piece.color(), Some(Bl));
+        /* g4 is only covered by the white pawn on f3 */
+        assert_eq!(game.defenders_vs_attackers(30), (0, 1));
+        assert_eq!(game.annotated_legal_moves().len(), 30);
+        assert!(game.check_evasions().is_empty());
+        assert_eq!(game.perft(1), 30);
+        assert_eq!(game.perft_divide(1).len(), 30);
+        assert_eq!(game.perft_cached(2), game.perft(2));
+        assert_eq!(game.pseudo_perft(1), 30);
+        assert_eq!(game.to_fen(), fen);
+
+        /* the position is the same as after switching the turn */
+        let mut switched = game.clone();
+        switched.switch_turn();
+        assert_eq!(fen, switched.to_fen());
+        assert_eq!(fen.split(' ').nth(1), Some("b"));
+        assert_eq!(game.zobrist_hash(), switched.zobrist_hash());
+        assert_eq!(game.to_bytes(), switched.to_bytes());
+        assert_eq!(game.repetition_count(), 1);
+
+        /* white can't move twice in a row, black's move switches first */
+        let mut played = game.clone();
+        assert_eq!(played.make_move(&ChessMove::to(P(Wh), 11, 27)), Err(MoveError::NotYourTurn));
+        assert_eq!(played.make_move(&mate), Ok(()));
+        assert!(played.is_checkmate());
+        assert_eq!(played.turn(), Wh);
+        assert_eq!(played.move_history().len(), 4);
+
+        game.play_random_game(5, 1);
+        assert_eq!(game.move_history().len(), 4);
+        assert_eq!(game.move_history()[3].piece.color(), Some(Bl));
+    }
+
+    #[test]
+    fn mate_in_one_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert!(game.mate_in_one_moves().is_empty());
+
+        /* back rank mate */
+        game.load_board([
+            None, None, None, None, R(Wh), None,  K(Wh), None,
+            None, None, None, None, None,  P(Wh), P(Wh), P(Wh),
+            None, None, None, None, None,  None,  None,  None,
+            None, None, None, None, None,  None,  None,  None,
+            None, None, None, None, None,  None,  None,  None,
+            None, None, None, None, None,  None,  None,  None,
+            None, None, None, None, None,  P(Bl), P(Bl), P(Bl),
+            None, None, None, None, None,  None,  K(Bl), None,
+        ]);
+        assert_eq!(game.mate_in_one_moves(), vec![ChessMove::to(R(Wh), 4, 60)]);
+    }
+
+    #[test]
+    fn result() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 13, 21), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(P(Wh), 14, 30),
+        ]).unwrap();
+        assert_eq!(game.result(), GameResult::Ongoing);
+        game.make_move(&ChessMove::to(Q(Bl), 59, 31)).unwrap();
+        assert_eq!(game.result(), GameResult::BlackWins);
+
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, N(Bl), K(Bl), None, None, None,
+        ]);
+        assert_eq!(game.result(), GameResult::Draw);
+    }
+
+    #[test]
+    fn stalemate() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  K(Bl), None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  R(Wh), None, None,
+            None, None, None,  None,  R(Wh), None,  None, None,
+            None, None, R(Wh), None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+        ]);
+        game.apply_move(&ChessMove::to(R(Wh), 21, 13)).unwrap();
+        game.switch_turn();
+
+        assert_eq!(game.status(), ChessState::Stalemate);
+        assert!(!game.is_check());
+        let turn = game.turn;
+        assert_eq!(game.get_legal_moves(&turn), Vec::new());
+        assert!(!game.has_legal_move(turn));
+        assert!(game.has_legal_move(turn.opposite()));
+        assert!(game.is_stalemate());
+    }
+
+    #[test]
+    fn en_passant() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None,  None,  None, None,  None,  None,  None, None,
+            P(Wh), None,  None, None,  P(Wh), None,  None, None,
+            None,  None,  None, None,  None,  None,  None, None,
+            None,  P(Bl), None, P(Bl), None,  P(Bl), None, None,
+            None,  None,  None, None,  None,  None,  None, None,
+            None,  None,  None, None,  None,  None,  None, None,
+            None,  None,  None, None,  None,  None,  None, None,
+            None,  None,  None, None,  None,  None,  None, None,
+        ]);
+        {
+            game.apply_move(&ChessMove::to(P(Wh), 8, 24)).unwrap();
+            let moves = game.get_legal_moves(&Bl);
+            assert!(moves.contains(&game.mv_en_passant(25, 16).unwrap()));
+        }
+
+        {
+            game.apply_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+            let moves = game.get_legal_moves(&Bl);
+            assert!(moves.contains(&game.mv_en_passant(27, 20).unwrap()));
+            assert!(moves.contains(&game.mv_en_passant(29, 20).unwrap()));
+
+            assert!(!moves.contains(&game.mv_en_passant(25, 16).unwrap()));
+        }
+
+        assert_eq!(game.mv_en_passant(27, 64), Err(MoveError::OutOfBounds));
+        assert_eq!(game.mv_en_passant(99, 20), Err(MoveError::OutOfBounds));
+        /* not a pawn, not diagonal, wrong direction and wrong rank */
+        assert_eq!(game.mv_en_passant(26, 19), Err(MoveError::IllegalMove));
+        assert_eq!(game.mv_en_passant(27, 19), Err(MoveError::IllegalMove));
+        assert_eq!(game.mv_en_passant(27, 36), Err(MoveError::IllegalMove));
+        assert_eq!(game.mv_en_passant(28, 37), Err(MoveError::IllegalMove));
+    }
+
+    #[test]
+    fn castling_1() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), N(Wh), None, None, K(Wh), None, None, R(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        let moves = game.get_legal_moves(&Wh);
+        assert!(moves.contains(&game.mv_castle(&Wh, false)));
+        assert!(!moves.contains(&game.mv_castle(&Wh, true)));
+    }
+
+    #[test]
+    fn castling_2() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None, None, None, K(Wh), None, None, R(Wh),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, Q(Bl), None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        let moves = game.get_legal_moves(&Wh);
+        assert!(moves.contains(&game.mv_castle(&Wh, false)));
+        assert!(!moves.contains(&game.mv_castle(&Wh, true)));
+
+        game.apply_move(&game.mv_castle(&Wh, false)).unwrap();
+        assert_eq!(game.get_board(), &[
+            R(Wh), None, None, None, None, R(Wh), K(Wh), None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, Q(Bl), None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
+        ]);
+    }
+
+    #[test]
+    fn castling_3() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, Q(Wh), None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            R(Bl), None, None, None, K(Bl), None, None, R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        let moves = game.get_legal_moves(&Bl);
+        assert!(!moves.contains(&game.mv_castle(&Bl, false)));
+        assert!(!moves.contains(&game.mv_castle(&Bl, true)));
+    }
+
+    #[test]
+    fn castling_4() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, Q(Wh), None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            R(Bl), None, None, None, K(Bl), None, None, R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        let moves = game.get_legal_moves(&Bl);
+        assert!(!moves.contains(&game.mv_castle(&Bl, false)));
+        assert!(moves.contains(&game.mv_castle(&Bl, true)));
+
+        game.apply_move(&game.mv_castle(&Bl, true)).unwrap();
+        assert_eq!(game.get_board(), &[
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, Q(Wh), None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, K(Bl), R(Bl), None, None, None, R(Bl),
+        ]);
+    }
+
+    #[test]
+    fn castling_attacked_squares() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        /* pawns attack the empty squares diagonally in front of them, even
+         * though they can't move there */
+        let board = [
+            R(Wh), None, None, None, K(Wh), None, None, R(Wh),
+            None,  None, None, None, None,  None, None, P(Bl),
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, K(Bl), None, None, None,
+        ];
+        let mut game = ChessGame::new();
+        game.load_board(board);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        let moves = game.get_legal_moves(&Wh);
+        assert!(!moves.contains(&game.mv_castle(&Wh, false)));
+        assert!(moves.contains(&game.mv_castle(&Wh, true)));
+
+        /* b1 is attacked, but the king doesn't pass it */
+        game.board[15] = None;
+        game.board[16] = N(Bl);
+        game.update_state_to_move();
+        let moves = game.get_legal_moves(&Wh);
+        assert!(moves.contains(&game.mv_castle(&Wh, false)));
+        assert!(moves.contains(&game.mv_castle(&Wh, true)));
+
+        /* no castling out of check */
+        game.board[16] = None;
+        game.board[21] = N(Bl);
+        game.update_state_to_move();
+        assert!(game.is_check_for(Wh));
+        assert!(game.get_legal_moves(&Wh).iter().all(|mv| !mv.castles));
+    }
+
+    #[test]
+    fn castling_custom_king() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None, None, K(Wh), None, None, None, R(Wh),
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            R(Bl), None, None, None,  None, K(Bl), None, R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        let white = [game.mv_castle(&Wh, false), game.mv_castle(&Wh, true)];
+        assert_eq!(white.map(|mv| (mv.origin, mv.target)), [(3, 6), (3, 2)]);
+        let black = [game.mv_castle(&Bl, false), game.mv_castle(&Bl, true)];
+        assert_eq!(black.map(|mv| (mv.origin, mv.target)), [(61, 62), (61, 58)]);
+        for mv in white.iter().chain(black.iter()) {
+            assert!(game.get_legal_moves(&mv.piece.color().unwrap()).contains(mv));
+        }
+
+        let mut kingside = game.clone();
+        kingside.make_move(&white[0]).unwrap();
+        assert_eq!(&kingside.get_board()[..8],
+                   &[R(Wh), None, None, None, None, R(Wh), K(Wh), None]);
+        let mut queenside = game.clone();
+        queenside.make_move(&white[1]).unwrap();
+        assert_eq!(&queenside.get_board()[..8],
+                   &[None, None, K(Wh), R(Wh), None, None, None, R(Wh)]);
+        queenside.make_move(&black[0]).unwrap();
+        assert_eq!(&queenside.get_board()[56..],
+                   &[R(Bl), None, None, None, None, R(Bl), K(Bl), None]);
+
+        /* the king passes e1 only when castling kingside */
+        game.board[36] = R(Bl);
+        game.update_state_to_move();
+        assert!(!game.get_legal_moves(&Wh).contains(&white[0]));
+        assert!(game.get_legal_moves(&Wh).contains(&white[1]));
+
+        /* no castling with the king off its back rank */
+        game.board[3] = None;
+        game.board[11] = K(Wh);
+        game.update_state_to_move();
+        assert!(game.get_legal_moves(&Wh).iter().all(|mv| !mv.castles));
+    }
+
+    #[test]
+    fn castling_rook_captured() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None, None, None, K(Wh), None, None, R(Wh),
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            R(Bl), None, None, None, K(Bl), None, None, R(Bl),
+        ]);
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
+
+        game.make_move(&ChessMove::captures(R(Wh), 0, 56, R(Bl))).unwrap();
+        assert!(!game.can_castle_q[Wh.index()]);
+        assert!(!game.can_castle_q[Bl.index()]);
+        assert!(game.can_castle_k[Wh.index()] && game.can_castle_k[Bl.index()]);
+        assert!(!game.get_legal_moves(&Bl).contains(&game.mv_castle(&Bl, true)));
+    }
 
     #[test]
-    fn literally_redundant() {
+    fn promotion() {
         use ChessPiece::*;
         use ChessColor::*;
 
-        let game = ChessGame::new();
-        assert_eq!(*game.get_board(), [
-            R(Wh), N(Wh), B(Wh), Q(Wh), K(Wh), B(Wh), N(Wh), R(Wh),
-            P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh), P(Wh),
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
             None, None, None, None, None, None, None, None,
             None, None, None, None, None, None, None, None,
             None, None, None, None, None, None, None, None,
             None, None, None, None, None, None, None, None,
-            P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl), P(Bl),
-            R(Bl), N(Bl), B(Bl), Q(Bl), K(Bl), B(Bl), N(Bl), R(Bl),
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, P(Wh), None,
+            None, None, None, None, K(Bl), B(Bl), None, R(Bl),
         ]);
+
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
+        {
+            let moves2: HashSet<ChessMove>
+                = game.mv_promotion(ChessMove::to(P(Wh), 54, 62)).into_iter().collect();
+            assert!(moves.is_superset(&moves2))
+        }
+        {
+            let moves2: HashSet<ChessMove>
+                = game.mv_promotion(ChessMove::captures(P(Wh), 54, 61, B(Bl))).into_iter().collect();
+            assert!(moves.is_superset(&moves2))
+        }
+        {
+            let moves2: HashSet<ChessMove>
+                = game.mv_promotion(ChessMove::captures(P(Wh), 54, 63, R(Bl))).into_iter().collect();
+            assert!(moves.is_superset(&moves2))
+        }
     }
 
     #[test]
-    fn pawn_moves() {
+    fn material_detail() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        let detail = game.material_detail();
+        assert_eq!(detail.pawns, [8, 8]);
+        assert_eq!(detail.knights, [2, 2]);
+        assert_eq!(detail.queens, [1, 1]);
+        assert_eq!(detail.kings, [1, 1]);
+        assert_eq!(detail.balance, 0);
+
         game.load_board([
-            None,  None, None,  None, None,  None,  None, None,
-            P(Wh), None, P(Wh), None, P(Wh), P(Wh), None, None,
-            None,  None, None,  None, P(Bl), None,  None, None,
-            P(Wh), None, None,  None, None,  None,  None, None,
-            None,  None, None,  None, None,  None,  None, None,
-            None,  None, P(Wh), None, None,  None,  None, None,
-            None,  None, None,  None, None,  None,  None, None,
-            None,  None, None,  None, None,  None,  None, None,
+            None, None, None, None, K(Wh), None, None, None,
+            P(Wh), P(Wh), None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, P(Bl), None,
+            None, N(Bl), None, None, K(Bl), None, None, R(Wh),
         ]);
+        let detail = game.material_detail();
+        assert_eq!(detail.pawns, [2, 1]);
+        assert_eq!(detail.rooks, [1, 0]);
+        assert_eq!(detail.knights, [0, 1]);
+        assert_eq!(detail.bishops, [0, 0]);
+        assert_eq!(detail.balance, 200 + 500 - 100 - 300);
+    }
 
-        /* Make this not depend on order somehow */
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(P(Wh), 8, 16),
-            ChessMove::to(P(Wh), 10, 18),
-            ChessMove::to(P(Wh), 10, 26),
-            ChessMove::to(P(Wh), 13, 21),
-            ChessMove::to(P(Wh), 13, 29),
-            ChessMove::to(P(Wh), 24, 32),
-            ChessMove::captures(P(Wh), 13, 20, P(Bl)),
-            ChessMove::to(P(Wh), 42, 50),
-        ]));
+    #[test]
+    fn material() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.material(Wh), 39);
+        assert_eq!(game.material(Bl), 39);
+        assert_eq!(game.material_balance(), 0);
+        assert_eq!(game.piece_count(P(Bl)), 8);
+        assert_eq!(game.piece_count(Q(Wh)), 1);
+        assert_eq!(game.piece_count(None), 32);
+
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            P(Wh), P(Wh), None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, P(Bl), None,
+            None, N(Bl), None, None, K(Bl), None, None, R(Wh),
+        ]);
+        assert_eq!(game.material(Wh), 7);
+        assert_eq!(game.material(Bl), 4);
+        assert_eq!(game.material_balance(), 3);
+        assert_eq!(100*game.material_balance(), game.material_detail().balance);
+        assert_eq!(game.piece_count(K(Bl)), 1);
+        assert_eq!(game.piece_count(B(Wh)), 0);
     }
 
     #[test]
-    fn rook_moves() {
+    fn material_signature() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        assert_eq!(game.material_signature(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+
         game.load_board([
-            None, None, None, None,  None,  None, None, None,
-            None, None, None, None,  None,  None, None, None,
-            None, None, None, None,  None,  None, None, None,
-            None, None, None, R(Wh), P(Wh), None, None, None,
-            None, None, None, None,  None,  None, None, None,
-            None, None, None, None,  None,  None, None, None,
-            None, None, None, B(Bl), None,  None, None, None,
-            None, None, None, None,  None,  None, None, None,
+            None,  None,  None, None, K(Wh), None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  P(Wh), None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, Q(Wh), None, None, None,
+            R(Bl), None,  None, None, K(Bl), None, None, None,
         ]);
+        assert_eq!(game.material_signature(), "KQPvKR");
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(P(Wh), 28, 36),
+        game.load_board([None; 64]);
+        assert_eq!(game.material_signature(), "v");
+    }
 
-            ChessMove::to(R(Wh), 27, 19),
-            ChessMove::to(R(Wh), 27, 11),
-            ChessMove::to(R(Wh), 27, 3),
+    #[test]
+    fn check_for() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-            ChessMove::to(R(Wh), 27, 35),
-            ChessMove::to(R(Wh), 27, 43),
-            ChessMove::captures(R(Wh), 27, 51, B(Bl)),
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, B(Wh), None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, K(Bl), None, None, R(Bl),
+        ]);
 
-            ChessMove::to(R(Wh), 27, 26),
-            ChessMove::to(R(Wh), 27, 25),
-            ChessMove::to(R(Wh), 27, 24),
-        ]));
+        assert_eq!(game.king_position(Wh), Some(4));
+        assert_eq!(game.king_position(Bl), Some(60));
+        assert!(game.is_check_for(Bl));
+        assert!(!game.is_check_for(Wh));
+
+        assert!(game.is_square_attacked(7, Bl));
+        assert!(!game.is_square_attacked(3, Bl));
+        assert!(game.is_square_attacked(59, Bl));
+        assert!(game.is_square_attacked(51, Wh));
+        assert!(!game.is_square_attacked(52, Wh));
     }
 
     #[test]
-    fn knight_moves() {
+    fn legal_moves_of_type() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert_eq!(R(Bl).kind(), Some(PieceKind::Rook));
+        assert_eq!(None.kind(), Option::None);
+        assert_eq!(PieceKind::Rook.with_color(Bl), R(Bl));
+        assert_eq!(PieceKind::Pawn.with_color(Wh), P(Wh));
+
+        let game = ChessGame::new();
+        let moves = game.legal_moves_of_type(Bl, PieceKind::Knight);
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|mv| mv.piece == N(Bl)));
+        assert_eq!(game.legal_moves_of_type(Wh, PieceKind::Pawn).len(), 16);
+        assert!(game.legal_moves_of_type(Wh, PieceKind::Queen).is_empty());
+    }
+
+    #[test]
+    fn legal_destinations() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        assert_eq!(game.legal_destinations(6), 1 << 21 | 1 << 23);
+        assert_eq!(game.legal_moves_from(6).len(), 2);
+        assert_eq!(game.legal_destinations(4), 0);
+        assert_eq!(game.legal_destinations(30), 0);
+        assert_eq!(game.legal_destinations(62), 0);
+        assert!(game.piece_has_moves(6) && game.piece_has_moves(12));
+        assert!(!game.piece_has_moves(4) && !game.piece_has_moves(30));
+        assert!(!game.piece_has_moves(62));
+
         game.load_board([
-            None, None, None,  None,  None,  None, None, None,
-            None, None, N(Wh), None,  None,  None, None, None,
-            None, None, None,  None,  P(Wh), None, None, None,
-            None, None, None,  B(Bl), None,  None, None, None,
-            None, None, None,  None,  None,  None, None, None,
-            None, None, None,  None,  None,  None, None, None,
-            None, None, None,  None,  None,  None, None, None,
-            None, None, None,  None,  None,  None, None, None,
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, P(Wh), None,
+            None, None, None, None, K(Bl), B(Bl), None, R(Bl),
         ]);
+        assert_eq!(game.legal_moves_from(54).len(), 12);
+        assert_eq!(game.legal_destinations(54), 1 << 61 | 1 << 62 | 1 << 63);
+    }
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(N(Wh), 10, 0),
-            ChessMove::to(N(Wh), 10, 4),
-            ChessMove::to(N(Wh), 10, 16),
-            ChessMove::to(N(Wh), 10, 25),
-            ChessMove::captures(N(Wh), 10, 27, B(Bl)),
+    #[test]
+    fn new_with_turn() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-            ChessMove::to(P(Wh), 20, 28),
-            ChessMove::captures(P(Wh), 20, 27, B(Bl)),
-        ]));
+        let mut game = ChessGame::new_with_turn(Bl);
+        assert_eq!(game.turn(), Bl);
+        assert_eq!(game.get_legal_moves(&Bl).len(), 20);
+        assert_eq!(game.make_move(&ChessMove::to(P(Wh), 12, 28)),
+                   Err(MoveError::NotYourTurn));
+        assert_eq!(game.make_move(&ChessMove::to(P(Bl), 52, 36)), Ok(()));
+        assert_eq!(game.turn(), Wh);
     }
 
     #[test]
-    fn bishop_moves() {
+    fn legal_moves_sorted() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
         game.load_board([
-            None,  None, None, None,  None,  None, None, None,
-            None,  None, None, None,  None,  None, None, None,
-            None,  None, None, None,  None,  None, None, None,
-            None,  None, None, B(Wh), None,  None, None, None,
-            None,  None, None, None,  P(Wh), None, None, None,
-            None,  None, None, None,  None,  None, None, None,
-            B(Bl), None, None, None,  None,  None, None, None,
-            None,  None, None, None,  None,  None, None, None,
+            None,  None,  None, None, K(Wh), None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            None,  None,  None, None, None,  None, None, None,
+            P(Wh), None,  None, None, None,  None, None, None,
+            None,  N(Bl), None, None, None,  None, None, K(Bl),
         ]);
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(P(Wh), 36, 44),
+        let moves = game.legal_moves_sorted(Wh);
+        assert_eq!(moves.len(), 13);
+        assert!(moves.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(moves[0], ChessMove::to(K(Wh), 4, 3));
+        /* promotions are ordered by target square before the promoted piece */
+        assert_eq!(moves[5].target, 56);
+        assert_eq!(moves[9].target, 57);
+        assert_eq!(moves[5..9].iter().map(|mv| mv.promotes).collect::<Vec<_>>(),
+                   vec![R(Wh), N(Wh), B(Wh), Q(Wh)]);
+    }
 
-            ChessMove::to(B(Wh), 27, 18),
-            ChessMove::to(B(Wh), 27, 9),
-            ChessMove::to(B(Wh), 27, 0),
+    #[test]
+    fn legal_moves_except() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-            ChessMove::to(B(Wh), 27, 34),
-            ChessMove::to(B(Wh), 27, 41),
-            ChessMove::captures(B(Wh), 27, 48, B(Bl)),
+        let game = ChessGame::new();
+        assert_eq!(game.legal_moves_except(Wh, &[]), game.get_legal_moves(&Wh));
+
+        let excluded = [ChessMove::to(P(Wh), 12, 28), ChessMove::to(N(Wh), 6, 21),
+                        ChessMove::to(P(Bl), 52, 36)];
+        let moves = game.legal_moves_except(Wh, &excluded);
+        assert_eq!(moves.len(), 18);
+        assert!(!moves.contains(&excluded[0]));
+        assert!(!moves.contains(&excluded[1]));
+        assert!(moves.contains(&ChessMove::to(P(Wh), 11, 27)));
+        assert_eq!(game.legal_moves_except(Bl, &excluded).len(), 19);
+    }
 
-            ChessMove::to(B(Wh), 27, 20),
-            ChessMove::to(B(Wh), 27, 13),
-            ChessMove::to(B(Wh), 27, 6),
-        ]));
+    #[test]
+    fn move_order() {
+        use std::collections::BTreeMap;
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mv = ChessMove::to(P(Wh), 12, 28);
+        assert!(ChessMove::to(P(Wh), 12, 20) < mv);
+        assert!(mv < ChessMove { promotes: Q(Wh), ..mv });
+        assert!(ChessMove::to(N(Wh), 6, 21) < mv);
+        assert!(mv < ChessMove { en_passant: true, ..mv });
+        assert!(ChessMove { castles: false, ..mv } < ChessMove { castles: true, ..mv });
+        assert_eq!(mv.cmp(&mv.annotated(MoveAnnotation::Good)), std::cmp::Ordering::Equal);
+
+        let mut counts = BTreeMap::new();
+        for mv in ChessGame::new().get_legal_moves(&Wh) {
+            *counts.entry(mv.annotated(MoveAnnotation::Dubious)).or_insert(0) += 1;
+            *counts.entry(mv).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 20);
+        assert!(counts.values().all(|n| *n == 2));
+        assert_eq!(counts.keys().next(), Some(&ChessMove::to(N(Wh), 1, 16)));
     }
 
     #[test]
-    fn queen_moves() {
+    fn color_index() {
+        use ChessColor::*;
+
+        assert_eq!(Wh.index(), 0);
+        assert_eq!(Bl.index(), 1);
+        assert_eq!(ChessColor::from_index(Bl.index()), Some(Bl));
+        assert_eq!(ChessColor::from_index(Wh.index()), Some(Wh));
+        assert_eq!(ChessColor::from_index(2), Option::None);
+    }
+
+    #[test]
+    fn place_pieces() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.place_pieces(&[(4, K(Wh)), (60, K(Bl)), (0, R(Wh))]).unwrap();
+        assert_eq!(game.get_board().iter().filter(|x| **x != None).count(), 3);
+        assert_eq!(game.get_board()[0], R(Wh));
+        assert_eq!(game.get_legal_moves(&Wh).len(), 5 + 10);
+
+        assert_eq!(game.place_pieces(&[(4, K(Wh)), (64, K(Bl))]), Err(MoveError::OutOfBounds));
+        assert_eq!(game.get_board()[0], R(Wh));
+    }
+
+    #[test]
+    fn switch_turn() {
+        let mut game = ChessGame::new();
+        assert_eq!(game.turn(), ChessColor::Wh);
+        assert_eq!(game.switch_turn().turn(), ChessColor::Bl);
+        assert_eq!(game.switch_turn().switch_turn().turn(), ChessColor::Bl);
+    }
+
+    #[test]
+    fn from_move_list() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(N(Wh), 6, 21),
+        ]).unwrap();
+        assert_eq!(game.turn(), Bl);
+        assert_eq!(game.move_history().len(), 3);
+        assert_eq!(game.get_board()[21], N(Wh));
+
+        assert_eq!(ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Wh), 11, 27),
+        ]).unwrap_err(), MoveError::InMoveList {
+            index: 1, error: Box::new(MoveError::NotYourTurn),
+        });
+        assert_eq!(ChessGame::from_move_list(&[
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Bl), 52, 28),
+        ]).unwrap_err(), MoveError::InMoveList {
+            index: 1, error: Box::new(MoveError::IllegalMove),
+        });
+    }
+
+    #[test]
+    fn clone_with_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        let next = game.clone_with_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        assert_eq!(next.get_board()[28], P(Wh));
+        assert_eq!(next.turn(), Bl);
+        assert_eq!(game.get_board()[28], None);
+        assert_eq!(game.turn(), Wh);
+
+        let games: Result<Vec<ChessGame>, MoveError> = game.get_legal_moves(&Wh)
+            .iter()
+            .map(|mv| game.clone_with_move(mv))
+            .collect();
+        assert_eq!(games.unwrap().len(), 20);
+        assert_eq!(game.clone_with_move(&ChessMove::to(P(Bl), 52, 36)).unwrap_err(),
+                   MoveError::NotYourTurn);
+    }
+
+    #[test]
+    fn self_capture() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let mv = ChessMove::captures(R(Wh), 0, 8, P(Wh));
+        assert_eq!(game.make_move(&mv), Err(MoveError::SelfCapture));
+        assert_eq!(game.get_board()[8], P(Wh));
+        assert_eq!(game.turn(), Wh);
+    }
+
+    #[test]
+    fn king_capture() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
         game.load_board([
-            None,  None, None, None,  None,  None, None,  None,
-            None,  None, None, None,  None,  None, None,  None,
-            None,  None, None, P(Wh), None,  None, None,  None,
-            None,  None, None, Q(Wh), None,  None, B(Bl), None,
-            None,  None, None, None,  P(Wh), None, None,  None,
-            None,  None, None, None,  None,  None, None,  None,
-            B(Bl), None, None, None,  None,  None, None,  None,
-            None,  None, None, None,  None,  None, None,  None,
+            None, None, None, None, K(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, R(Wh), None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, None,  None, None, None,
+            None, None, None, None, K(Bl), None, None, None,
         ]);
+        let mv = ChessMove::captures(R(Wh), 36, 60, K(Bl));
+        assert_eq!(game.make_move(&mv), Err(MoveError::IllegalKingCapture));
+        assert_eq!(game.get_board()[60], K(Bl));
+    }
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(P(Wh), 36, 44),
+    #[test]
+    fn apply_move_errors() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-            ChessMove::to(Q(Wh), 27, 18),
-            ChessMove::to(Q(Wh), 27, 9),
-            ChessMove::to(Q(Wh), 27, 0),
+        let mut game = ChessGame::new();
+        let fen = game.to_fen();
+        for (mv, error) in [
+            (ChessMove::to(N(Wh), 2, 18), MoveError::WrongPiece),
+            (ChessMove::to(None, 16, 24), MoveError::WrongPiece),
+            (ChessMove::to(P(Wh), 12, 64), MoveError::OutOfBounds),
+            (ChessMove::to(P(Wh), 70, 28), MoveError::OutOfBounds),
+        ] {
+            assert_eq!(game.apply_move(&mv), Err(error));
+            assert_eq!(game.to_fen(), fen);
+            assert!(game.move_history().is_empty());
+        }
 
-            ChessMove::to(Q(Wh), 27, 34),
-            ChessMove::to(Q(Wh), 27, 41),
-            ChessMove::captures(Q(Wh), 27, 48, B(Bl)),
+        /* not checked for legality, nor whose turn it is */
+        assert_eq!(game.apply_move(&ChessMove::to(P(Bl), 52, 28)), Ok(()));
+        assert_eq!(game.get_board()[28], P(Bl));
+    }
+
+    #[test]
+    fn move_rules() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-            ChessMove::to(Q(Wh), 27, 20),
-            ChessMove::to(Q(Wh), 27, 13),
-            ChessMove::to(Q(Wh), 27, 6),
+        let mut game = ChessGame::new();
+        game.apply_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        game.switch_turn();
+        assert_eq!(game.halfmove_clock, 0);
 
-            ChessMove::to(Q(Wh), 27, 26),
-            ChessMove::to(Q(Wh), 27, 25),
-            ChessMove::to(Q(Wh), 27, 24),
+        let shuffle = [
+            ChessMove::to(N(Bl), 62, 45), ChessMove::to(N(Wh), 6, 21),
+            ChessMove::to(N(Bl), 45, 62), ChessMove::to(N(Wh), 21, 6),
+        ];
+        for i in 0..150 {
+            assert!(game.is_fifty_move_claimable() == (i >= 100));
+            assert!(game.is_ended() == (i >= 100));
+            assert!((game.result() == GameResult::Draw) == (i >= 100));
+            assert_eq!(game.halfmove_clock(), i as u32);
+            assert!(!game.is_seventyfive_move_draw());
+            game.apply_move(&shuffle[i % 4]).unwrap();
+            game.switch_turn();
+        }
+        assert_eq!(game.halfmove_clock, 150);
+        assert!(game.is_seventyfive_move_draw());
 
-            ChessMove::to(Q(Wh), 27, 28),
-            ChessMove::to(Q(Wh), 27, 29),
-            ChessMove::captures(Q(Wh), 27, 30, B(Bl)),
+        game.apply_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        assert_eq!(game.halfmove_clock, 0);
+        assert!(!game.is_fifty_move_claimable());
+        assert!(!game.is_ended());
+    }
 
-            ChessMove::to(Q(Wh), 27, 35),
-            ChessMove::to(Q(Wh), 27, 43),
-            ChessMove::to(Q(Wh), 27, 51),
-            ChessMove::to(Q(Wh), 27, 59),
-        ]));
+    #[test]
+    fn fifty_move_result() {
+        let game = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert!(!game.is_ended());
+        assert_eq!(game.result(), GameResult::Ongoing);
+
+        let game = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert!(game.is_ended());
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.draw_reason(), Some(DrawReason::FiftyMoveRule));
+
+        /* a random game stops there too */
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 90 80").unwrap();
+        assert_eq!(game.play_random_game(3, 50), GameResult::Draw);
+        assert!(game.halfmove_clock() <= 100);
     }
 
     #[test]
-    fn king_moves() {
+    fn last_irreversible_ply() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
-        game.load_board([
-            None, None, None,  None,  None, None, None, None,
-            None, None, None,  None,  None, None, None, None,
-            None, None, None,  P(Wh), None, None, None, None,
-            None, None, B(Bl), K(Wh), None, None, None, None,
-            None, None, None,  None,  None, None, None, None,
-            None, None, None,  None,  None, None, None, None,
-            None, None, None,  None,  None, None, None, None,
-            None, None, None,  None,  None, None, None, None,
-        ]);
+        assert_eq!(game.last_irreversible_ply(), 0);
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        assert_eq!(moves, HashSet::from([
-            ChessMove::to(K(Wh), 27, 18),
-            ChessMove::to(K(Wh), 27, 20),
-            ChessMove::to(K(Wh), 27, 28),
-            ChessMove::to(K(Wh), 27, 36),
-            ChessMove::to(K(Wh), 27, 34),
-            ChessMove::captures(K(Wh), 27, 26, B(Bl)),
+        game.make_move(&ChessMove::to(N(Wh), 6, 21)).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 0);
 
-            ChessMove::captures(P(Wh), 19, 26, B(Bl)),
-        ]));
+        game.make_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        game.make_move(&ChessMove::to(N(Wh), 21, 6)).unwrap();
+        game.make_move(&ChessMove::to(N(Bl), 57, 42)).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 2);
+
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 5);
+
+        /* black is to move right after white's move, before switching */
+        let mut game = ChessGame::new();
+        game.apply_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 1);
+        game.switch_turn();
+        assert_eq!(game.last_irreversible_ply(), 1);
+
+        let mut game = ChessGame::new();
+        game.fullmove_number = 0;
+        assert_eq!(game.last_irreversible_ply(), 0);
+        game.turn = Bl;
+        assert_eq!(game.last_irreversible_ply(), 1);
     }
 
     #[test]
-    fn checkmate() {
+    fn neighbor() {
+        assert_eq!(super::neighbor(12, 1, 2), Some(29));
+        assert_eq!(super::neighbor(12, -4, 0), Some(8));
+        assert_eq!(super::neighbor(7, 1, 0), Option::None);
+        assert_eq!(super::neighbor(60, 0, 1), Option::None);
+        assert_eq!(super::neighbor(64, 0, -1), Option::None);
+    }
+
+    #[test]
+    fn en_passant_square_for() {
         use ChessPiece::*;
         use ChessColor::*;
 
-        let mut game = ChessGame::new();
-        game.load_board([
-            None, None, None,  K(Bl), None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  R(Wh), None, None,
-            None, None, None,  None,  R(Wh), None,  None, None,
-            None, None, R(Wh), None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-        ]);
-        game.apply_move(&ChessMove::to(R(Wh), 21, 19));
-        game.switch_turn();
+        assert_eq!(ChessGame::en_passant_square_for(28, Wh), Some(20));
+        assert_eq!(ChessGame::en_passant_square_for(24, Wh), Some(16));
+        assert_eq!(ChessGame::en_passant_square_for(36, Bl), Some(44));
+        assert_eq!(ChessGame::en_passant_square_for(39, Bl), Some(47));
+        assert_eq!(ChessGame::en_passant_square_for(3, Wh), Option::None);
+        assert_eq!(ChessGame::en_passant_square_for(60, Bl), Option::None);
+        assert_eq!(ChessGame::en_passant_square_for(64, Wh), Option::None);
 
-        assert_eq!(game.state, ChessState::Check);
-        let turn = game.turn;
-        assert_eq!(game.get_legal_moves(&turn), Vec::new());
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 11, 27)).unwrap();
+        assert_eq!(game.en_passant_target(), ChessGame::en_passant_square_for(27, Wh));
+        game.make_move(&ChessMove::to(P(Bl), 53, 37)).unwrap();
+        assert_eq!(game.en_passant_target(), ChessGame::en_passant_square_for(37, Bl));
+        assert_eq!(game.en_passant_target(), Some(45));
     }
 
     #[test]
-    fn stalemate() {
+    fn castling_move() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        assert_eq!(game.castling_move(Wh, true), Option::None);
+        assert_eq!(game.castling_move(Wh, false), Option::None);
+
+        /* black can't castle queenside through the attacked d8 */
         game.load_board([
-            None, None, None,  K(Bl), None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  R(Wh), None, None,
-            None, None, None,  None,  R(Wh), None,  None, None,
-            None, None, R(Wh), None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
-            None, None, None,  None,  None,  None,  None, None,
+            R(Wh), None, None, None,  K(Wh), None, None, R(Wh),
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, R(Wh), None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            None,  None, None, None,  None,  None, None, None,
+            R(Bl), None, None, None,  K(Bl), None, None, R(Bl),
         ]);
-        game.apply_move(&ChessMove::to(R(Wh), 21, 13));
-        game.switch_turn();
+        game.set_all_castle_eligibility([true; 2], [true; 2]);
 
-        assert_eq!(game.state, ChessState::Normal);
-        let turn = game.turn;
-        assert_eq!(game.get_legal_moves(&turn), Vec::new());
+        let mv = game.castling_move(Wh, true).unwrap();
+        assert!(mv.castles);
+        assert_eq!((mv.origin, mv.target), (4, 6));
+        assert_eq!(game.castling_move(Wh, false).map(|mv| mv.target), Some(2));
+        assert_eq!(game.castling_move(Bl, true).map(|mv| mv.target), Some(62));
+        assert_eq!(game.castling_move(Bl, false), Option::None);
     }
 
     #[test]
-    fn en_passant() {
+    fn at_ply() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
-        game.load_board([
-            None,  None,  None, None,  None,  None,  None, None,
-            P(Wh), None,  None, None,  P(Wh), None,  None, None,
-            None,  None,  None, None,  None,  None,  None, None,
-            None,  P(Bl), None, P(Bl), None,  P(Bl), None, None,
-            None,  None,  None, None,  None,  None,  None, None,
-            None,  None,  None, None,  None,  None,  None, None,
-            None,  None,  None, None,  None,  None,  None, None,
-            None,  None,  None, None,  None,  None,  None, None,
-        ]);
-        {
-            game.apply_move(&ChessMove::to(P(Wh), 8, 24));
-            let moves = game.get_legal_moves(&Bl);
-            assert!(moves.contains(&game.mv_en_passant(25, 16)));
+        let moves = [
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(N(Wh), 6, 21), ChessMove::to(N(Bl), 57, 42),
+        ];
+        let mut boards = vec![*game.get_board()];
+        for mv in moves.iter() {
+            game.make_move(mv).unwrap();
+            boards.push(*game.get_board());
         }
 
-        {
-            game.apply_move(&ChessMove::to(P(Wh), 12, 28));
-            let moves = game.get_legal_moves(&Bl);
-            assert!(moves.contains(&game.mv_en_passant(27, 20)));
-            assert!(moves.contains(&game.mv_en_passant(29, 20)));
-
-            assert!(!moves.contains(&game.mv_en_passant(25, 16)));
+        for (n, board) in boards.iter().enumerate() {
+            let earlier = game.at_ply(n);
+            assert_eq!(earlier.get_board(), board);
+            assert_eq!(earlier.move_history(), moves[..n]);
+            assert_eq!(earlier.turn(), if n % 2 == 0 { Wh } else { Bl });
         }
+        assert_eq!(game.at_ply(0).get_legal_moves(&Wh).len(), 20);
+        assert_eq!(game.at_ply(1).en_passant_target(), Some(20));
+        assert_eq!(game.at_ply(100).get_board(), game.get_board());
+
+        /* going back and playing on */
+        let mut earlier = game.at_ply(2);
+        earlier.make_move(&ChessMove::to(P(Wh), 11, 27)).unwrap();
+        assert_eq!(earlier.move_history().len(), 3);
     }
 
     #[test]
-    fn castling_1() {
+    fn status() {
         use ChessPiece::*;
         use ChessColor::*;
 
+        /* fool's mate */
         let mut game = ChessGame::new();
-        game.load_board([
-            R(Wh), N(Wh), None, None, K(Wh), None, None, R(Wh),
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, K(Bl), None, None, None,
-        ]);
-        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        assert_eq!(game.status(), ChessState::Normal);
+        game.make_move(&ChessMove::to(P(Wh), 13, 21)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 52, 36)).unwrap();
+        game.make_move(&ChessMove::to(P(Wh), 14, 30)).unwrap();
+        assert_eq!(game.status(), ChessState::Normal);
+        game.make_move(&ChessMove::to(Q(Bl), 59, 31)).unwrap();
+        assert_eq!(game.status(), ChessState::Checkmate);
+
+        /* a check that can be blocked */
+        let mut game = ChessGame::new();
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        game.make_move(&ChessMove::to(P(Bl), 53, 45)).unwrap();
+        game.make_move(&ChessMove::to(Q(Wh), 3, 39)).unwrap();
+        assert_eq!(game.status(), ChessState::Check);
+        game.make_move(&ChessMove::to(P(Bl), 54, 46)).unwrap();
+        assert_eq!(game.status(), ChessState::Normal);
+    }
 
-        let moves = game.get_legal_moves(&Wh);
-        assert!(moves.contains(&game.mv_castle(&Wh, false)));
-        assert!(!moves.contains(&game.mv_castle(&Wh, true)));
+    #[test]
+    fn piece_at() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        assert_eq!(game.piece_at('e', 1), Some(K(Wh)));
+        assert_eq!(game.piece_at('d', 8), Some(Q(Bl)));
+        assert_eq!(game.piece_at('e', 4), Option::None);
+        assert_eq!(game.piece_at('i', 1), Option::None);
+        assert_eq!(game.piece_at('a', 9), Option::None);
+        assert_eq!(game.piece_at_index(6), Some(N(Wh)));
+        assert_eq!(game.piece_at_index(28), Option::None);
+        assert_eq!(game.piece_at_index(64), Option::None);
     }
 
     #[test]
-    fn castling_2() {
+    fn undo_move() {
+        let mut game = ChessGame::new();
+        assert_eq!(game.undo_move(), None);
+
+        /* a double push, en passant, castling on both sides and a capture
+         * with promotion */
+        let mut fens = vec![game.to_fen()];
+        let mut moves = vec![];
+        for (origin, target) in [
+            (12, 28), (57, 42), (28, 36), (51, 35), (36, 43), (58, 37), (6, 21),
+            (59, 51), (5, 26), (60, 58), (4, 6), (48, 40), (43, 50), (40, 32), (50, 59),
+        ] {
+            let mv = *game.legal_moves_from(origin)
+                          .iter()
+                          .find(|mv| mv.target == target)
+                          .unwrap();
+            game.make_move(&mv).unwrap();
+            fens.push(game.to_fen());
+            moves.push(mv);
+        }
+        assert!(moves[4].en_passant && moves[9].castles && moves[10].castles);
+        assert!(moves[14].promotes != ChessPiece::None);
+
+        while let Some(mv) = game.undo_move() {
+            assert_eq!(Some(mv), moves.pop());
+            fens.pop();
+            assert_eq!(Some(game.to_fen()), fens.last().cloned());
+            let turn = game.turn();
+            assert_eq!(game.get_legal_moves(&turn),
+                       ChessGame::from_fen(&game.to_fen()).unwrap().get_legal_moves(&turn));
+        }
+        assert_eq!(game.to_fen(), ChessGame::new().to_fen());
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    fn defenders_vs_attackers() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        assert_eq!(game.defenders_vs_attackers(20), (2, 0));
+        assert_eq!(game.defenders_vs_attackers(44), (0, 2));
+        assert_eq!(game.defenders_vs_attackers(28), (0, 0));
+
+        /* e5 is attacked by white's knight and pawn and black's bishop and
+         * queen, but not by the rook behind the queen */
         game.load_board([
-            R(Wh), None, None, None, K(Wh), None, None, R(Wh),
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, Q(Bl), None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, K(Bl), None, None, None,
+            None, None, None, None,  K(Wh), None,  None,  None,
+            None, None, None, None,  None,  None,  None,  None,
+            None, None, None, None,  None,  N(Wh), None,  None,
+            None, None, None, P(Wh), None,  None,  None,  None,
+            None, None, None, None,  None,  None,  None,  None,
+            None, None, None, None,  None,  None,  None,  None,
+            None, None, None, None,  Q(Bl), None,  B(Bl), None,
+            None, None, None, None,  R(Bl), None,  None,  K(Bl),
         ]);
-        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        assert_eq!(game.defenders_vs_attackers(36), (2, 2));
+        game.switch_turn();
+        assert_eq!(game.defenders_vs_attackers(36), (2, 2));
+        assert_eq!(game.defenders_vs_attackers(52), (1, 0));
+        assert_eq!(game.defenders_vs_attackers(27), (1, 1));
+    }
 
-        let moves = game.get_legal_moves(&Wh);
-        assert!(moves.contains(&game.mv_castle(&Wh, false)));
-        assert!(!moves.contains(&game.mv_castle(&Wh, true)));
+    #[test]
+    fn promotion_choices() {
+        use ChessPiece::*;
+        use ChessColor::*;
 
-        game.apply_move(&game.mv_castle(&Wh, false));
-        assert_eq!(game.get_board(), &[
-            R(Wh), None, None, None, None, R(Wh), K(Wh), None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, Q(Bl), None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, K(Bl), None, None, None,
+        let mut game = ChessGame::new();
+        assert!(game.promotion_choices(12, 28).is_empty());
+
+        game.load_board([
+            None, None, None,  None,  K(Wh), None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, None,  None,  None,  None, None, None,
+            None, None, P(Wh), None,  None,  None, None, P(Wh),
+            None, None, None,  R(Bl), None,  None, K(Bl), N(Bl),
         ]);
+        let choices = game.promotion_choices(50, 59);
+        assert_eq!(choices.len(), 4);
+        assert!(choices.iter().all(|mv| mv.captures == R(Bl)));
+        let mut promotes: Vec<ChessPiece> = choices.iter().map(|mv| mv.promotes).collect();
+        promotes.sort();
+        assert_eq!(promotes, [R(Wh), N(Wh), B(Wh), Q(Wh)]);
+        assert_eq!(game.promotion_choices(50, 58).len(), 4);
+
+        /* blocked, and then not white's turn */
+        assert!(game.promotion_choices(55, 63).is_empty());
+        game.switch_turn();
+        assert!(game.promotion_choices(50, 59).is_empty());
     }
 
     #[test]
-    fn castling_3() {
+    fn all_legal_moves() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
-        game.load_board([
-            None, None, None, None, K(Wh), None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, Q(Wh), None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            R(Bl), None, None, None, K(Bl), None, None, R(Bl),
-        ]);
-        game.set_all_castle_eligibility([true; 2], [true; 2]);
+        game.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        let moves = game.all_legal_moves();
+        assert_eq!(moves[Wh.index()], game.get_legal_moves(&Wh));
+        assert_eq!(moves[Bl.index()], game.get_legal_moves(&Bl));
+        assert_eq!(moves[Bl.index()].len(), 20);
+        /* white's moves as if it were white's turn again */
+        assert_eq!(moves[Wh.index()].len(), 30);
+    }
 
-        let moves = game.get_legal_moves(&Bl);
-        assert!(!moves.contains(&game.mv_castle(&Bl, false)));
-        assert!(!moves.contains(&game.mv_castle(&Bl, true)));
+    #[test]
+    fn pawn_ranks() {
+        use ChessColor::*;
+
+        assert_eq!((Wh.pawn_start_rank(), Wh.promotion_rank()), (1, 7));
+        assert_eq!((Bl.pawn_start_rank(), Bl.promotion_rank()), (6, 0));
+
+        let start: Vec<usize> = (0..64).filter(|i| Wh.is_pawn_start_square(*i)).collect();
+        assert_eq!(start, (8..16).collect::<Vec<_>>());
+        let start: Vec<usize> = (0..64).filter(|i| Bl.is_pawn_start_square(*i)).collect();
+        assert_eq!(start, (48..56).collect::<Vec<_>>());
+
+        assert!(!Wh.is_promotion_square(55) && Wh.is_promotion_square(56));
+        assert!(Wh.is_promotion_square(63) && !Wh.is_promotion_square(7));
+        assert!(Bl.is_promotion_square(0) && Bl.is_promotion_square(7));
+        assert!(!Bl.is_promotion_square(8) && !Bl.is_promotion_square(56));
     }
 
     #[test]
-    fn castling_4() {
+    fn is_quiet() {
         use ChessPiece::*;
         use ChessColor::*;
 
         let mut game = ChessGame::new();
+        assert!(game.get_legal_moves(&Wh).iter().all(|mv| game.is_quiet(mv)));
+
         game.load_board([
-            None, None, None, None, K(Wh), None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, Q(Wh), None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            R(Bl), None, None, None, K(Bl), None, None, R(Bl),
+            None,  None,  None,  None,  K(Wh), None,  None, R(Wh),
+            None,  None,  None,  None,  None,  None,  None, None,
+            None,  None,  None,  None,  None,  None,  None, None,
+            None,  None,  B(Wh), None,  None,  None,  None, None,
+            None,  None,  None,  P(Bl), None,  None,  None, None,
+            None,  None,  None,  None,  None,  None,  None, None,
+            P(Wh), None,  None,  None,  None,  None,  None, None,
+            None,  R(Wh), None,  N(Wh), None,  K(Bl), None, None,
         ]);
-        game.set_all_castle_eligibility([true; 2], [true; 2]);
-
-        let moves = game.get_legal_moves(&Bl);
-        assert!(!moves.contains(&game.mv_castle(&Bl, false)));
-        assert!(moves.contains(&game.mv_castle(&Bl, true)));
+        game.set_castle_eligibility(&Wh, false, true);
+
+        let capture = ChessMove::captures(B(Wh), 26, 35, P(Bl));
+        let mut promotion = ChessMove::to(P(Wh), 48, 56);
+        promotion.promotes = N(Wh);
+        let check = ChessMove::to(R(Wh), 7, 63);
+        let discovered = ChessMove::to(N(Wh), 59, 42);
+        let castle = game.castling_move(Wh, true).unwrap();
+        let quiet = ChessMove::to(B(Wh), 26, 17);
+        for mv in [capture, promotion, check, discovered, castle, quiet] {
+            assert!(game.get_legal_moves(&Wh).contains(&mv));
+        }
 
-        game.apply_move(&game.mv_castle(&Bl, true));
-        assert_eq!(game.get_board(), &[
-            None, None, None, None, K(Wh), None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, Q(Wh), None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, K(Bl), R(Bl), None, None, None, R(Bl),
-        ]);
+        assert!(!game.is_quiet(&capture));
+        assert!(!game.move_gives_check(&capture));
+        assert!(!game.is_quiet(&promotion));
+        assert!(!game.move_gives_check(&promotion));
+        for mv in [check, discovered, castle] {
+            assert!(game.move_gives_check(&mv));
+            assert!(!game.is_quiet(&mv));
+        }
+        assert!(game.is_quiet(&quiet));
     }
 
     #[test]
-    fn promotion() {
+    fn board_diff() {
         use ChessPiece::*;
         use ChessColor::*;
 
+        let game = ChessGame::new();
+        assert!(game.board_diff(&game).is_empty());
+        assert!(game.board_diff(&ChessGame::new_with_turn(Bl)).is_empty());
+
+        let mut next = game.clone();
+        next.make_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        assert_eq!(game.board_diff(&next), [(12, P(Wh), None), (28, None, P(Wh))]);
+        assert_eq!(next.board_diff(&game), [(12, None, P(Wh)), (28, P(Wh), None)]);
+
+        /* castling moves two pieces */
         let mut game = ChessGame::new();
         game.load_board([
-            None, None, None, None, K(Wh), None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, P(Wh), None,
-            None, None, None, None, K(Bl), B(Bl), None, R(Bl),
+            R(Wh), None, None, None, K(Wh), None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, K(Bl), None, None, None,
+        ]);
+        game.set_castle_eligibility(&Wh, true, true);
+        let next = game.clone_with_move(&game.castling_move(Wh, false).unwrap()).unwrap();
+        assert_eq!(game.board_diff(&next), [
+            (0, R(Wh), None), (2, None, K(Wh)), (3, None, R(Wh)), (4, K(Wh), None),
         ]);
+    }
 
-        let moves: HashSet<ChessMove> = game.get_legal_moves(&game.turn).into_iter().collect();
-        {
-            let moves2: HashSet<ChessMove>
-                = game.mv_promotion(ChessMove::to(P(Wh), 54, 62)).into_iter().collect();
-            assert!(moves.is_superset(&moves2))
-        }
-        {
-            let moves2: HashSet<ChessMove>
-                = game.mv_promotion(ChessMove::captures(P(Wh), 54, 61, B(Bl))).into_iter().collect();
-            assert!(moves.is_superset(&moves2))
-        }
-        {
-            let moves2: HashSet<ChessMove>
-                = game.mv_promotion(ChessMove::captures(P(Wh), 54, 63, R(Bl))).into_iter().collect();
-            assert!(moves.is_superset(&moves2))
-        }
+    #[test]
+    fn directions() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert!(R(Wh).is_slider() && B(Bl).is_slider() && Q(Wh).is_slider());
+        assert!(!P(Wh).is_slider() && !N(Bl).is_slider() && !K(Wh).is_slider());
+        assert!(!None.is_slider());
+
+        assert_eq!(R(Wh).directions().len(), 4);
+        assert_eq!(B(Wh).directions().len(), 4);
+        assert_eq!(Q(Bl).directions().len(), 8);
+        assert_eq!(K(Bl).directions(), Q(Wh).directions());
+        assert!(N(Wh).directions().contains(&(-2, 1)));
+        assert_eq!(P(Wh).directions(), &[(1, 1), (-1, 1)]);
+        assert_eq!(P(Bl).directions(), &[(1, -1), (-1, -1)]);
+        assert!(None.directions().is_empty());
     }
 
     #[test]
@@ -1313,4 +4245,22 @@ mod tests {
         m2.promotes = B(Wh);
         assert_eq!(m2.algebraic(), "a5xb6(B)");
     }
+
+    #[test]
+    fn annotation() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        let mv = ChessMove::to(P(Wh), 12, 28).annotated(MoveAnnotation::Dubious);
+        assert_eq!(mv.algebraic(), "e2e4?!");
+        assert_eq!(mv.annotated(MoveAnnotation::from_nag(14)).algebraic(), "e2e4 $14");
+        assert_eq!(MoveAnnotation::from_nag(3), MoveAnnotation::Brilliant);
+        assert_eq!(MoveAnnotation::Brilliant.nag(), 3);
+
+        /* legal moves still match annotated ones */
+        assert!(game.get_legal_moves(&Wh).contains(&mv));
+        let moves: HashSet<ChessMove> = game.get_legal_moves(&Wh).into_iter().collect();
+        assert!(moves.contains(&mv));
+    }
 }
@@ -0,0 +1,93 @@
+/**
+ * Builds a `[ChessPiece; 64]` from an 8x8 grid of pieces drawn the way the
+ * board is usually shown, with rank 8 at the top and the a-file to the left.
+ * White pieces are uppercase letters (`P N B R Q K`), black pieces lowercase
+ * and empty squares `.`. The grid is turned into the a1 = 0 order used by
+ * [crate::ChessGame::load_board].
+ *
+ * Anything else than those letters and dots fails to compile, and so does a
+ * grid without exactly 64 squares.
+ *
+ * ```text
+ * let board = board![
+ *     r n b q k b n r
+ *     p p p p p p p p
+ *     . . . . . . . .
+ *     . . . . . . . .
+ *     . . . . . . . .
+ *     . . . . . . . .
+ *     P P P P P P P P
+ *     R N B Q K B N R
+ * ];
+ * ```
+ */
+#[macro_export]
+macro_rules! board {
+    ($($piece:tt)*) => {{
+        let grid: [$crate::ChessPiece; 64] = [$($crate::board_piece!($piece)),*];
+        let mut board = [$crate::ChessPiece::None; 64];
+        let mut i = 0;
+        while i < 64 {
+            board[(7 - i / 8) * 8 + i % 8] = grid[i];
+            i += 1;
+        }
+        board
+    }};
+}
+
+/* one square of `board!` */
+#[doc(hidden)]
+#[macro_export]
+macro_rules! board_piece {
+    (.) => { $crate::ChessPiece::None };
+    (P) => { $crate::ChessPiece::P($crate::ChessColor::Wh) };
+    (R) => { $crate::ChessPiece::R($crate::ChessColor::Wh) };
+    (N) => { $crate::ChessPiece::N($crate::ChessColor::Wh) };
+    (B) => { $crate::ChessPiece::B($crate::ChessColor::Wh) };
+    (Q) => { $crate::ChessPiece::Q($crate::ChessColor::Wh) };
+    (K) => { $crate::ChessPiece::K($crate::ChessColor::Wh) };
+    (p) => { $crate::ChessPiece::P($crate::ChessColor::Bl) };
+    (r) => { $crate::ChessPiece::R($crate::ChessColor::Bl) };
+    (n) => { $crate::ChessPiece::N($crate::ChessColor::Bl) };
+    (b) => { $crate::ChessPiece::B($crate::ChessColor::Bl) };
+    (q) => { $crate::ChessPiece::Q($crate::ChessColor::Bl) };
+    (k) => { $crate::ChessPiece::K($crate::ChessColor::Bl) };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn board() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert_eq!(&board![
+            r n b q k b n r
+            p p p p p p p p
+            . . . . . . . .
+            . . . . . . . .
+            . . . . . . . .
+            . . . . . . . .
+            P P P P P P P P
+            R N B Q K B N R
+        ], ChessGame::new().get_board());
+
+        let board = board![
+            . . . . k . . .
+            . . . . . . P .
+            . . . . . . . .
+            . . . . . . . .
+            . . . q . . . .
+            . . . . . . . .
+            . . . . . . . .
+            K . . . . . . .
+        ];
+        assert_eq!(board[0], K(Wh));
+        assert_eq!(board[60], K(Bl));
+        assert_eq!(board[54], P(Wh));
+        assert_eq!(board[27], Q(Bl));
+        assert_eq!(board.iter().filter(|x| **x != None).count(), 4);
+    }
+}
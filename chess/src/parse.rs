@@ -0,0 +1,265 @@
+use crate::square::parse_square;
+use crate::{ChessGame, ChessMove, ChessPiece, PieceKind};
+
+/**
+ * Reasons text can't be turned into a move, see [ChessGame::parse_move].
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum MoveParseError {
+    /** The text is not a move in any supported notation. */
+    InvalidFormat,
+    /** The text describes a move that is not legal for the side to move. */
+    IllegalMove,
+    /**
+     * The text matches more than one legal move, e.g. `Nd2` when both
+     * knights can go there.
+     */
+    Ambiguous,
+}
+
+/**
+ * Another name for [MoveParseError], the error of [ChessGame::parse_move].
+ */
+pub type ParseMoveError = MoveParseError;
+
+/* the kind of piece written as `c` in SAN (uppercase) or UCI promotions
+ * (lowercase) */
+fn piece_kind(c: char) -> Option<PieceKind> {
+    return match c.to_ascii_uppercase() {
+        'R' => Some(PieceKind::Rook),
+        'N' => Some(PieceKind::Knight),
+        'B' => Some(PieceKind::Bishop),
+        'Q' => Some(PieceKind::Queen),
+        'K' => Some(PieceKind::King),
+        _ => None,
+    };
+}
+
+/* whether `s` has the shape of a UCI move like "e2e4" or "e7e8q" */
+fn looks_like_uci(s: &str) -> bool {
+    return s.is_ascii()
+        && (s.len() == 4 || s.len() == 5)
+        && parse_square(&s[0..2]).is_some()
+        && parse_square(&s[2..4]).is_some();
+}
+
+impl ChessGame {
+    /* the single legal move of the side to move matching `filter` */
+    fn unique_move(&self, filter: impl Fn(&ChessMove) -> bool)
+                   -> Result<ChessMove, MoveParseError> {
+        let mut matching = self.next_moves[self.side_to_move().index()]
+                               .iter()
+                               .filter(|mv| filter(mv));
+        return match (matching.next(), matching.next()) {
+            (Some(mv), Option::None) => Ok(*mv),
+            (Some(_), Some(_)) => Err(MoveParseError::Ambiguous),
+            _ => Err(MoveParseError::IllegalMove),
+        };
+    }
+
+    /**
+     * Finds the legal move of the side to move written in UCI coordinate
+     * notation, i.e. the origin and target squares followed by the promoted
+     * piece if any, e.g. `e2e4`, `e1g1` (castling) or `e7e8q`.
+     */
+    pub fn move_from_uci(&self, s: &str) -> Result<ChessMove, MoveParseError> {
+        if !looks_like_uci(s) {
+            return Err(MoveParseError::InvalidFormat);
+        }
+        let origin = parse_square(&s[0..2]).unwrap();
+        let target = parse_square(&s[2..4]).unwrap();
+        let promotes = match s[4..].chars().next() {
+            Some(c) => match piece_kind(c) {
+                Some(PieceKind::King) | Option::None => return Err(MoveParseError::InvalidFormat),
+                kind => kind,
+            },
+            _ => Option::None,
+        };
+
+        return self.unique_move(|mv| {
+            mv.origin == origin && mv.target == target && mv.promotes.kind() == promotes
+        });
+    }
+
+    /**
+     * Finds the legal move of the side to move written in Standard Algebraic
+     * Notation, e.g. `Nf3`, `exd5`, `O-O` or `e8=Q+`. Check and mate
+     * suffixes and annotation glyphs are optional, castling may be written
+     * with zeros, the promotion `=` may be left out and more disambiguation
+     * than needed (e.g. `Ng1f3`) is accepted.
+     */
+    pub fn parse_san(&self, s: &str) -> Result<ChessMove, MoveParseError> {
+        if let Some(mv) = self.san_to_move_map().get(s) {
+            return Ok(*mv);
+        }
+
+        let s = s.trim_end_matches(['+', '#', '!', '?']);
+        match s {
+            "O-O" | "0-0" => {
+                return self.unique_move(|mv| mv.castles && !mv.castles_queenside());
+            },
+            "O-O-O" | "0-0-0" => return self.unique_move(|mv| mv.castles_queenside()),
+            _ => (),
+        }
+        if !s.is_ascii() || s.len() < 2 {
+            return Err(MoveParseError::InvalidFormat);
+        }
+
+        /* the piece letter, then the disambiguation and capture, then the
+         * target square and the promotion. Piece letters are uppercase so
+         * that e.g. "b8=Q" is a pawn move on the b-file, not a bishop's */
+        let piece = s.chars().next().filter(|c| c.is_ascii_uppercase());
+        let (kind, rest) = match piece.and_then(piece_kind) {
+            Some(kind) => (kind, &s[1..]),
+            _ => (PieceKind::Pawn, s),
+        };
+        let (rest, promotes) = match rest.chars().last().and_then(piece_kind) {
+            Some(promotes) if kind == PieceKind::Pawn => {
+                (rest[..rest.len() - 1].trim_end_matches('='), Some(promotes))
+            },
+            _ => (rest, Option::None),
+        };
+        if rest.len() < 2 {
+            return Err(MoveParseError::InvalidFormat);
+        }
+        let target = parse_square(&rest[rest.len() - 2..]).ok_or(MoveParseError::InvalidFormat)?;
+        let prefix = &rest[..rest.len() - 2];
+        let (prefix, captures) = match prefix.strip_suffix('x') {
+            Some(prefix) => (prefix, true),
+            _ => (prefix, false),
+        };
+
+        let mut file = Option::None;
+        let mut rank = Option::None;
+        for c in prefix.chars() {
+            match c {
+                'a'..='h' if file.is_none() && rank.is_none() => {
+                    file = Some(c as usize - 'a' as usize);
+                },
+                '1'..='8' if rank.is_none() => rank = Some(c as usize - '1' as usize),
+                _ => return Err(MoveParseError::InvalidFormat),
+            }
+        }
+
+        return self.unique_move(|mv| {
+            !mv.castles
+                && mv.piece.kind() == Some(kind)
+                && mv.target == target
+                && mv.promotes.kind() == promotes
+                && (!captures || mv.captures != ChessPiece::None)
+                && file.is_none_or(|file| mv.origin % 8 == file)
+                && rank.is_none_or(|rank| mv.origin / 8 == rank)
+        });
+    }
+
+    /**
+     * Finds the legal move of the side to move written in either UCI
+     * coordinate notation (see [ChessGame::move_from_uci]) or Standard
+     * Algebraic Notation (see [ChessGame::parse_san]). Text shaped like two
+     * squares and an optional promotion is read as UCI, anything else as
+     * SAN.
+     */
+    pub fn parse_move(&self, s: &str) -> Result<ChessMove, MoveParseError> {
+        let s = s.trim();
+        if looks_like_uci(s) {
+            return self.move_from_uci(s);
+        }
+        return self.parse_san(s);
+    }
+
+    /**
+     * The former name of [ChessGame::parse_move], which it calls.
+     */
+    #[deprecated(note = "renamed to `parse_move`")]
+    pub fn interpret_move(&self, s: &str) -> Result<ChessMove, MoveParseError> {
+        return self.parse_move(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn parse_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        let e4 = ChessMove::to(P(Wh), 12, 28);
+        let nf3 = ChessMove::to(N(Wh), 6, 21);
+        assert_eq!(game.parse_move("e2e4"), Ok(e4));
+        assert_eq!(game.parse_move("e4"), Ok(e4));
+        assert_eq!(game.parse_move(" Nf3 "), Ok(nf3));
+        assert_eq!(game.parse_move("g1f3"), Ok(nf3));
+        assert_eq!(game.parse_move("Ng1f3"), Ok(nf3));
+        assert_eq!(game.parse_move("Nf3!?"), Ok(nf3));
+        assert_eq!(game.parse_move("e2e5"), Err(MoveParseError::IllegalMove));
+        assert_eq!(game.parse_move("Nd4"), Err(MoveParseError::IllegalMove));
+        assert_eq!(game.parse_move("e7e5"), Err(MoveParseError::IllegalMove));
+        assert_eq!(game.parse_move("hello"), Err(MoveParseError::InvalidFormat));
+        assert_eq!(game.parse_move("e2e4k"), Err(MoveParseError::InvalidFormat));
+        assert_eq!(game.parse_move(""), Err(ParseMoveError::InvalidFormat));
+        #[allow(deprecated)]
+        let interpreted = game.interpret_move("Nf3");
+        assert_eq!(interpreted, Ok(nf3));
+
+        /* both knights reach d2, the pawn can capture or promote */
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, N(Wh), None,  None,  K(Wh), None,  None, R(Wh),
+            None, None,  None,  None,  None,  None,  None, None,
+            None, None,  None,  None,  None,  N(Wh), None, None,
+            None, None,  None,  None,  None,  None,  None, None,
+            None, None,  None,  None,  None,  None,  None, None,
+            None, None,  None,  None,  None,  None,  None, None,
+            None, None,  P(Wh), None,  None,  None,  None, None,
+            None, None,  None,  R(Bl), K(Bl), None,  None, None,
+        ]);
+        game.set_castle_eligibility(&Wh, false, true);
+        assert_eq!(game.parse_move("Nd2"), Err(MoveParseError::Ambiguous));
+        assert_eq!(game.parse_move("Nbd2"), Ok(ChessMove::to(N(Wh), 1, 11)));
+        assert_eq!(game.parse_move("N3d2"), Ok(ChessMove::to(N(Wh), 21, 11)));
+        assert_eq!(game.parse_move("O-O"), Ok(game.castling_move(Wh, true).unwrap()));
+        assert_eq!(game.parse_move("0-0"), game.parse_move("e1g1"));
+        assert_eq!(game.parse_move("O-O-O"), Err(MoveParseError::IllegalMove));
+
+        let mut promotion = ChessMove::captures(P(Wh), 50, 59, R(Bl));
+        promotion.promotes = Q(Wh);
+        assert_eq!(game.parse_move("cxd8=Q+"), Ok(promotion));
+        assert_eq!(game.parse_move("cxd8Q"), Ok(promotion));
+        assert_eq!(game.parse_move("c7d8q"), Ok(promotion));
+        assert_eq!(game.parse_move("cxd8"), Err(MoveParseError::IllegalMove));
+        assert_eq!(game.parse_move("c7d8"), Err(MoveParseError::IllegalMove));
+        assert_eq!(game.parse_move("c8=N").map(|mv| mv.promotes), Ok(N(Wh)));
+
+        /* the en passant flag comes from the legal move */
+        let game = ChessGame::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        for s in ["exd6", "e5d6"] {
+            let mv = game.parse_move(s).unwrap();
+            assert!(mv.en_passant && mv.captures == P(Bl), "{s}");
+        }
+
+        /* pawns on the b-file aren't bishops, with or without the check
+         * suffix the generated SAN has */
+        let mut game = ChessGame::new();
+        game.load_board([
+            None,  None,  None,  None, K(Wh), None, None, None,
+            None,  P(Wh), None,  None, None,  None, None, None,
+            None,  None,  N(Bl), None, None,  None, None, None,
+            None,  None,  None,  None, None,  None, None, None,
+            None,  None,  None,  None, None,  None, None, None,
+            None,  None,  None,  None, None,  None, None, None,
+            None,  P(Wh), None,  None, None,  None, None, None,
+            N(Bl), None,  None,  None, K(Bl), None, None, None,
+        ]);
+        let squares = |s| game.parse_move(s).map(|mv| (mv.origin, mv.target, mv.promotes));
+        assert_eq!(squares("b8=Q"), Ok((49, 57, Q(Wh))));
+        assert_eq!(squares("b8Q"), Ok((49, 57, Q(Wh))));
+        assert_eq!(squares("bxa8=Q"), Ok((49, 56, Q(Wh))));
+        assert_eq!(squares("bxa8N"), Ok((49, 56, N(Wh))));
+        assert_eq!(squares("b4"), Ok((9, 25, None)));
+        assert_eq!(squares("bxc3"), Ok((9, 18, None)));
+        assert_eq!(squares("Bxc3"), Err(MoveParseError::IllegalMove));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{ChessColor, ChessGame, ChessPiece};
+
+/**
+ * Pawn structure of one side, see [ChessGame::pawn_structure].
+ */
+#[derive(Debug,Copy,Clone,Default,PartialEq,Eq)]
+pub struct PawnStructure {
+    /**
+     * Pawns on a file beyond the first, so two pawns on a file count as one
+     * doubled pawn and three as two.
+     */
+    pub doubled: u32,
+    /** Pawns without a friendly pawn on either adjacent file. */
+    pub isolated: u32,
+    /** Groups of adjacent files that all have at least one pawn. */
+    pub islands: u32,
+}
+
+/* the pawns of one side per file, see [ChessGame::pawn_files] */
+#[derive(Debug,Copy,Clone,Default)]
+pub(crate) struct PawnFiles {
+    /* the number of pawns on each file */
+    counts: [u32; 8],
+    /* the rank (0 to 7) of the pawn furthest from promotion on each file,
+     * which is the one any enemy pawn on that file or next to it has to
+     * get past */
+    rearmost: [Option<usize>; 8],
+}
+
+impl PawnFiles {
+    /* the structure made up by these pawns */
+    pub(crate) fn structure(&self) -> PawnStructure {
+        let files = self.counts;
+        let mut out = PawnStructure::default();
+        for file in 0..8 {
+            if files[file] == 0 {
+                continue;
+            }
+            let left = file > 0 && files[file - 1] > 0;
+            let right = file < 7 && files[file + 1] > 0;
+
+            out.doubled += files[file] - 1;
+            if !left && !right {
+                out.isolated += files[file];
+            }
+            if !left {
+                out.islands += 1;
+            }
+        }
+        return out;
+    }
+}
+
+/* whether a pawn of `color` on `square` is passed, given the pawn files of
+ * both sides from [ChessGame::pawn_files]. Doesn't check that the pawn is
+ * actually there */
+pub(crate) fn is_passed(files: &[PawnFiles; 2], square: usize, color: ChessColor) -> bool {
+    let enemy = &files[color.opposite().index()];
+    let (x, y) = (square % 8, (square / 8) as isize);
+    return (x.saturating_sub(1)..=(x + 1).min(7)).all(|file| {
+        return enemy.rearmost[file].is_none_or(|rank| (rank as isize - y) * color.dir() <= 0);
+    });
+}
+
+impl ChessGame {
+    /* the pawns of both sides per file, indexed by [ChessColor::index], in
+     * a single pass over the board */
+    pub(crate) fn pawn_files(&self) -> [PawnFiles; 2] {
+        let mut files = [PawnFiles::default(); 2];
+        for (i, piece) in self.board.iter().enumerate() {
+            let color = match piece {
+                ChessPiece::P(color) => *color,
+                _ => continue,
+            };
+            let side = &mut files[color.index()];
+            let (file, rank) = (i % 8, i / 8);
+            side.counts[file] += 1;
+            /* white pawns are scanned from rank 1 up, so the first one found
+             * is the rearmost, while for black it is the last one */
+            if color == ChessColor::Bl || side.rearmost[file].is_none() {
+                side.rearmost[file] = Some(rank);
+            }
+        }
+        return files;
+    }
+
+    /**
+     * Gets the squares of `color`'s passed pawns, i.e. pawns with no enemy
+     * pawn in front of them on the same or an adjacent file. White pawns
+     * advance towards rank 8 and black pawns towards rank 1.
+     */
+    pub fn passed_pawns(&self, color: ChessColor) -> Vec<usize> {
+        let files = self.pawn_files();
+        return (0..64).filter(|i| {
+            self.board[*i] == ChessPiece::P(color) && is_passed(&files, *i, color)
+        }).collect();
+    }
+
+    /**
+     * Describes the pawn structure of `color`, counting doubled pawns,
+     * isolated pawns and pawn islands as defined in [PawnStructure].
+     */
+    pub fn pawn_structure(&self, color: ChessColor) -> PawnStructure {
+        return self.pawn_files()[color.index()].structure();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn passed_pawns() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert!(game.passed_pawns(Wh).is_empty());
+        assert!(game.passed_pawns(Bl).is_empty());
+
+        /* the pawn on e5 is passed and protected by d4, d4 is held back by
+         * the pawn on c6, the pawns on h3 and h4 block each other and the
+         * black pawn on a3 is passed as well */
+        game.load_board([
+            None,  None, None,  None,  K(Wh), None, None, None,
+            None,  None, None,  None,  None,  None, None, None,
+            P(Bl), None, None,  None,  None,  None, None, P(Wh),
+            None,  None, None,  P(Wh), None,  None, None, P(Bl),
+            None,  None, None,  None,  P(Wh), None, None, None,
+            None,  None, P(Bl), None,  None,  None, None, None,
+            None,  None, None,  None,  None,  None, None, None,
+            None,  None, None,  None,  K(Bl), None, None, None,
+        ]);
+        assert_eq!(game.passed_pawns(Wh), vec![36]);
+        assert_eq!(game.passed_pawns(Bl), vec![16]);
+
+        /* the same as checking every enemy pawn, in positions from random
+         * games */
+        for seed in 0..10 {
+            let mut game = ChessGame::new();
+            game.play_random_game(seed, 60);
+            for col in [Wh, Bl] {
+                let passed: Vec<usize> = (0..64).filter(|i| {
+                    game.board[*i] == P(col) && !(0..64).any(|j| {
+                        game.board[j] == P(col.opposite())
+                            && (j % 8).abs_diff(i % 8) <= 1
+                            && (j as isize / 8 - *i as isize / 8) * col.dir() > 0
+                    })
+                }).collect();
+                assert_eq!(game.passed_pawns(col), passed);
+            }
+        }
+    }
+
+    #[test]
+    fn pawn_structure() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(game.pawn_structure(Wh), PawnStructure {
+            doubled: 0, isolated: 0, islands: 1,
+        });
+
+        /* white has doubled pawns on a and d, isolated pawns on a and h and
+         * islands on a, c-d and h; black has a single island on f-h */
+        game.load_board([
+            None,  None, None,  None,  K(Wh), None,  None,  None,
+            P(Wh), None, P(Wh), P(Wh), None,  None,  None,  P(Wh),
+            P(Wh), None, None,  P(Wh), None,  None,  None,  None,
+            None,  None, None,  None,  None,  None,  None,  None,
+            None,  None, None,  None,  None,  None,  None,  None,
+            None,  None, None,  None,  None,  None,  P(Bl), None,
+            None,  None, None,  None,  None,  P(Bl), None,  P(Bl),
+            None,  None, None,  None,  K(Bl), None,  None,  None,
+        ]);
+        assert_eq!(game.pawn_structure(Wh), PawnStructure {
+            doubled: 2, isolated: 3, islands: 3,
+        });
+        assert_eq!(game.pawn_structure(Bl), PawnStructure {
+            doubled: 0, isolated: 0, islands: 1,
+        });
+    }
+}
@@ -0,0 +1,331 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::{ChessColor, ChessGame, FenError, MoveParseError};
+
+/* the longest line of movetext, as recommended by the PGN standard */
+const PGN_LINE_LENGTH: usize = 79;
+
+/**
+ * Reasons a game can be rejected by [ChessGame::from_pgn].
+ */
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum PgnError {
+    /** A tag pair is not of the form `[Name "value"]`. */
+    BadTag,
+    /** The value of the FEN tag is not a valid FEN string. */
+    BadFen(FenError),
+    /** A comment, variation or tag pair is never closed. */
+    Unterminated,
+    /** The move `san` by `color` at `move_number` could not be played. */
+    BadMove { move_number: u32, color: ChessColor, san: String, error: MoveParseError },
+}
+
+/* the pieces of PGN text that matter for replaying the game */
+enum PgnToken {
+    Tag(String, String),
+    Move(String),
+    Result,
+}
+
+/* skips everything up to and including `close`, which may be nested
+ * inside for variations */
+fn skip_until(chars: &mut Peekable<Chars>, open: char, close: char) -> Result<(), PgnError> {
+    let mut depth = 1;
+    for c in chars.by_ref() {
+        if c == open && open != close {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+    return Err(PgnError::Unterminated);
+}
+
+/* the tag pair after an opening `[`, up to and including the `]` */
+fn read_tag(chars: &mut Peekable<Chars>) -> Result<PgnToken, PgnError> {
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+        name.push(c);
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if name.is_empty() || chars.next() != Some('"') {
+        return Err(PgnError::BadTag);
+    }
+
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => value.extend(chars.next()),
+            Some(c) => value.push(c),
+            _ => return Err(PgnError::Unterminated),
+        }
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    return match chars.next() {
+        Some(']') => Ok(PgnToken::Tag(name, value)),
+        Some(_) => Err(PgnError::BadTag),
+        _ => Err(PgnError::Unterminated),
+    };
+}
+
+/* splits the first game in `pgn` into tags, moves and the result, leaving
+ * out move numbers, comments, NAGs and variations */
+fn tokenize(pgn: &str) -> Result<Vec<PgnToken>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = pgn.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => tokens.push(read_tag(&mut chars)?),
+            '{' => skip_until(&mut chars, '{', '}')?,
+            '(' => skip_until(&mut chars, '(', ')')?,
+            ';' | '%' => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            },
+            _ if c.is_whitespace() => (),
+            _ => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[{(;)".contains(*c)) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        tokens.push(PgnToken::Result);
+                        break;
+                    },
+                    _ if word.starts_with('$') => (),
+                    _ => {
+                        /* a move number, possibly written together with the
+                         * move after it like `1.e4` */
+                        let san = match word.rfind('.') {
+                            Some(dot) if word.starts_with(|c: char| c.is_ascii_digit()) => {
+                                &word[dot + 1..]
+                            },
+                            _ => &word,
+                        };
+                        if !san.is_empty() {
+                            tokens.push(PgnToken::Move(san.to_string()));
+                        }
+                    },
+                }
+            },
+        }
+    }
+    return Ok(tokens);
+}
+
+impl ChessGame {
+    /* the game termination marker of the current position */
+    fn pgn_result(&self) -> &'static str {
+        return if self.is_checkmate() {
+            match self.side_to_move() {
+                ChessColor::Wh => "0-1",
+                ChessColor::Bl => "1-0",
+            }
+        } else if self.is_draw() {
+            "1/2-1/2"
+        } else {
+            "*"
+        };
+    }
+
+    /**
+     * Exports the game in Portable Game Notation: the seven standard tags
+     * (with `?` for everything but the result), then every move of
+     * [ChessGame::move_history] in Standard Algebraic Notation with move
+     * numbers, and the result. The result is `1-0` or `0-1` after checkmate,
+     * `1/2-1/2` if [ChessGame::is_draw] and `*` otherwise. Games that didn't
+     * start from the standard position also get the `SetUp` and `FEN` tags.
+     *
+     * ```text
+     * [Event "?"]
+     * [Site "?"]
+     * [Date "????.??.??"]
+     * [Round "?"]
+     * [White "?"]
+     * [Black "?"]
+     * [Result "1-0"]
+     *
+     * 1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0
+     * ```
+     */
+    pub fn to_pgn(&self) -> String {
+        let result = self.pgn_result();
+        let mut out = String::new();
+        for (tag, value) in [("Event", "?"), ("Site", "?"), ("Date", "????.??.??"),
+                             ("Round", "?"), ("White", "?"), ("Black", "?"),
+                             ("Result", result)] {
+            out.push_str(&format!("[{tag} \"{value}\"]\n"));
+        }
+
+        let mut game = self.at_ply(0);
+        let fen = game.to_fen();
+        if fen != ChessGame::new().to_fen() {
+            out.push_str(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n"));
+        }
+        out.push('\n');
+
+        let mut tokens = Vec::new();
+        for (i, entry) in self.history.iter().enumerate() {
+            game.restore(&entry.position);
+            if game.turn == ChessColor::Wh {
+                tokens.push(format!("{}.", game.fullmove_number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", game.fullmove_number));
+            }
+            tokens.push(game.to_san(&entry.mv));
+        }
+        tokens.push(result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > PGN_LINE_LENGTH {
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        out.push_str(&line);
+        out.push('\n');
+
+        return out;
+    }
+
+    /**
+     * Replays the first game in the Portable Game Notation text `pgn`, as
+     * written by [ChessGame::to_pgn]. Moves may be in Standard Algebraic
+     * Notation or UCI notation (see [ChessGame::parse_move]), and the game
+     * starts from the position in the `FEN` tag if there is one. Comments,
+     * NAGs, annotation glyphs like `!?` and variations are skipped, and the
+     * other tags and the result are ignored.
+     *
+     * A move that can't be played in the position reached so far is
+     * rejected with [PgnError::BadMove], telling which move it was.
+     */
+    pub fn from_pgn(pgn: &str) -> Result<ChessGame, PgnError> {
+        let tokens = tokenize(pgn)?;
+
+        let mut game = ChessGame::new();
+        for token in tokens.iter() {
+            if let PgnToken::Tag(name, fen) = token {
+                if name == "FEN" {
+                    game = ChessGame::from_fen(fen).map_err(PgnError::BadFen)?;
+                }
+            }
+        }
+
+        for token in tokens.iter() {
+            if let PgnToken::Move(san) = token {
+                let mv = game.parse_move(san).map_err(|error| PgnError::BadMove {
+                    move_number: game.fullmove_number,
+                    color: game.turn,
+                    san: san.clone(),
+                    error,
+                })?;
+                game.make_move(&mv).expect("Parsed move was illegal");
+            }
+        }
+        return Ok(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn to_pgn() {
+        let mut game = ChessGame::new();
+        assert!(game.to_pgn().ends_with("[Result \"*\"]\n\n*\n"));
+
+        for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+            game.make_move(&game.parse_san(san).unwrap()).unwrap();
+        }
+        assert_eq!(game.to_pgn(), concat!(
+            "[Event \"?\"]\n",
+            "[Site \"?\"]\n",
+            "[Date \"????.??.??\"]\n",
+            "[Round \"?\"]\n",
+            "[White \"?\"]\n",
+            "[Black \"?\"]\n",
+            "[Result \"1-0\"]\n",
+            "\n",
+            "1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n",
+        ));
+
+        /* black to move first from a custom position, ending in stalemate */
+        let fen = "1k6/8/8/8/8/2Q5/8/7K b - - 0 40";
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        for san in ["Ka8", "Qc7"] {
+            game.make_move(&game.parse_san(san).unwrap()).unwrap();
+        }
+        assert!(game.to_pgn().ends_with(&format!(concat!(
+            "[Result \"1/2-1/2\"]\n",
+            "[SetUp \"1\"]\n",
+            "[FEN \"{}\"]\n",
+            "\n",
+            "40... Ka8 41. Qc7 1/2-1/2\n",
+        ), fen)));
+
+        /* long games are wrapped */
+        let mut game = ChessGame::new();
+        for _ in 0..10 {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                game.make_move(&game.parse_san(san).unwrap()).unwrap();
+            }
+        }
+        let pgn = game.to_pgn();
+        assert!(pgn.lines().all(|line| line.len() <= 79));
+        assert!(pgn.lines().nth(8).unwrap().starts_with("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3"));
+        assert!(pgn.ends_with(" 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn from_pgn() {
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+            game.make_move(&game.parse_san(san).unwrap()).unwrap();
+        }
+        let restored = ChessGame::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(restored.move_history(), game.move_history());
+        assert!(restored.is_checkmate());
+
+        let fen = "1k6/8/8/8/8/2Q5/8/7K b - - 0 40";
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        game.make_move(&game.parse_san("Ka8").unwrap()).unwrap();
+        let restored = ChessGame::from_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(restored.to_fen(), game.to_fen());
+        assert_eq!(restored.at_ply(0).to_fen(), fen);
+
+        /* comments, NAGs, variations, annotations and UCI moves */
+        let pgn = "[Event \"Test \\\"game\\\"\"]\n[Site \"?\"]\n\n\
+                   1.e4 {best by test} e5 $1 2. Nf3 (2. f4 exf4 (2... d5)) Nc6!? ; Ruy\n\
+                   3. f1b5 a6 4. Bxc6 dxc6 *";
+        let game = ChessGame::from_pgn(pgn).unwrap();
+        assert_eq!(game.san_moves(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6"]);
+        assert_eq!(ChessGame::from_pgn("").unwrap().to_fen(), ChessGame::new().to_fen());
+
+        assert_eq!(ChessGame::from_pgn("1. e4 e5 2. Ke3").err(), Some(PgnError::BadMove {
+            move_number: 2,
+            color: Wh,
+            san: "Ke3".to_string(),
+            error: MoveParseError::IllegalMove,
+        }));
+        assert!(matches!(ChessGame::from_pgn("1. e4 e5 2. Nc3 Nf6 3. Nd5 Nxd5 4. exd5 Xy2"),
+                         Err(PgnError::BadMove { move_number: 4, color: Bl, .. })));
+        assert_eq!(ChessGame::from_pgn("1. e4 {unfinished").err(), Some(PgnError::Unterminated));
+        assert_eq!(ChessGame::from_pgn("[Event]\n1. e4").err(), Some(PgnError::BadTag));
+        assert_eq!(ChessGame::from_pgn("[FEN \"8/8 w - - 0 1\"]").err(),
+                   Some(PgnError::BadFen(FenError::WrongRankCount(2))));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{ChessGame, ChessMove, GameResult};
+
+/* splitmix64, the same generator the Zobrist keys are made with */
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        return Rng { state: seed };
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    }
+
+    fn pick<'a>(&mut self, moves: &'a [ChessMove]) -> Option<&'a ChessMove> {
+        if moves.is_empty() {
+            return None;
+        }
+        return moves.get((self.next() % moves.len() as u64) as usize);
+    }
+}
+
+impl ChessGame {
+    /**
+     * Picks one of the legal moves of the side to move at random, or [None]
+     * if there are none. The same `seed` always picks the same move in the
+     * same position.
+     */
+    pub fn random_move(&self, seed: u64) -> Option<ChessMove> {
+        return Rng::new(seed).pick(&self.next_moves[self.side_to_move().index()]).copied();
+    }
+
+    /**
+     * Plays random legal moves with [ChessGame::make_move] until the game is
+     * over (see [ChessGame::result]) or `max_plies` moves have been played,
+     * and returns the result. The moves only depend on `seed` and the
+     * starting position, which makes this useful for fuzzing and generating
+     * test positions. If a move was applied without switching the turn, the
+     * turn is switched first.
+     */
+    pub fn play_random_game(&mut self, seed: u64, max_plies: u32) -> GameResult {
+        if self.moved_unswitched {
+            self.switch_turn();
+        }
+        let mut rng = Rng::new(seed);
+        for _ in 0..max_plies {
+            if self.result() != GameResult::Ongoing {
+                break;
+            }
+            let mv = *rng.pick(&self.next_moves[self.turn.index()]).unwrap();
+            self.make_move(&mv).unwrap();
+        }
+        return self.result();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn random_move() {
+        let game = ChessGame::new();
+        let mv = game.random_move(7).unwrap();
+        assert!(game.get_legal_moves(&ChessColor::Wh).contains(&mv));
+        assert_eq!(game.random_move(7), Some(mv));
+
+        let mut game = ChessGame::new();
+        game.load_board([ChessPiece::None; 64]);
+        assert_eq!(game.random_move(7), None);
+    }
+
+    #[test]
+    fn play_random_game() {
+        for seed in 0..20 {
+            let mut game = ChessGame::new();
+            let result = game.play_random_game(seed, 300);
+
+            let mut replay = ChessGame::new();
+            assert_eq!(replay.play_random_game(seed, 300), result);
+            assert_eq!(replay.move_history(), game.move_history());
+
+            assert!(game.move_history().len() <= 300);
+            assert_eq!(result, game.result());
+            assert_eq!(result != GameResult::Ongoing, game.is_ended());
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{ChessColor, ChessGame};
+
+/**
+ * Options for [ChessGame::ascii_board].
+ */
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub struct BoardPrintOptions {
+    /**
+     * The side shown at the bottom of the board. With black at the bottom the
+     * board is rotated, so both ranks and files are reversed.
+     */
+    pub bottom: ChessColor,
+    /**
+     * Whether to print rank numbers to the left and file letters below the
+     * board.
+     */
+    pub coordinates: bool,
+    /**
+     * Squares to highlight as a bitmask, where bit `i` is set to highlight
+     * square `i` (using the same indices as [ChessGame::get_board]).
+     */
+    pub highlights: u64,
+    /**
+     * Whether to draw a frame around the squares.
+     */
+    pub border: bool,
+}
+
+impl Default for BoardPrintOptions {
+    fn default() -> BoardPrintOptions {
+        return BoardPrintOptions {
+            bottom: ChessColor::Wh,
+            coordinates: true,
+            highlights: 0,
+            border: false,
+        };
+    }
+}
+
+impl ChessGame {
+    /**
+     * Renders the board as text, one line per rank. White pieces are upper
+     * case and black pieces lower case, using the letters from algebraic
+     * notation, and empty squares are `.`. Highlighted squares are
+     * surrounded by brackets. See [BoardPrintOptions], and
+     * [ChessGame::diagram] for an example with a border.
+     *
+     * The starting position with default options looks like this:
+     * ```text
+     * 8  r  n  b  q  k  b  n  r
+     * 7  p  p  p  p  p  p  p  p
+     * 6  .  .  .  .  .  .  .  .
+     * 5  .  .  .  .  .  .  .  .
+     * 4  .  .  .  .  .  .  .  .
+     * 3  .  .  .  .  .  .  .  .
+     * 2  P  P  P  P  P  P  P  P
+     * 1  R  N  B  Q  K  B  N  R
+     *    a  b  c  d  e  f  g  h
+     * ```
+     */
+    pub fn ascii_board(&self, options: BoardPrintOptions) -> String {
+        return self.render_board(options, |i| self.board[i].letter());
+    }
+
+    /* the board laid out like [ChessGame::ascii_board] describes, showing
+     * square `i` as `symbol(i)` */
+    fn render_board(&self, options: BoardPrintOptions, symbol: impl Fn(usize) -> char) -> String {
+        let flip = options.bottom == ChessColor::Bl;
+        let order = |i: usize| if flip { i } else { 7 - i };
+        let margin = if options.coordinates { "  " } else { "" };
+        let frame = format!("{margin}+{}+\n", "-".repeat(24));
+
+        let mut out = String::new();
+        if options.border {
+            out.push_str(&frame);
+        }
+        for row in 0..8 {
+            let y = order(row);
+            let mut line = String::new();
+            if options.coordinates {
+                line.push_str(&format!("{} ", y + 1));
+            }
+            if options.border {
+                line.push('|');
+            }
+            for col in 0..8 {
+                let x = 7 - order(col);
+                let i = 8*y + x;
+                let c = symbol(i);
+                if options.highlights & (1 << i) != 0 {
+                    line.push_str(&format!("[{c}]"));
+                } else {
+                    line.push_str(&format!(" {c} "));
+                }
+            }
+            if options.border {
+                line.push('|');
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        if options.border {
+            out.push_str(&frame);
+        }
+
+        if options.coordinates {
+            out.push_str(margin);
+            if options.border {
+                out.push(' ');
+            }
+            for col in 0..8 {
+                let x = 7 - order(col);
+                out.push_str(&format!(" {} ", char::from(b'a' + x as u8)));
+            }
+            out.truncate(out.trim_end().len());
+            out.push('\n');
+        }
+
+        return out;
+    }
+
+    /**
+     * Renders the board with a border and coordinates, highlighting the
+     * squares in `highlight` (e.g. the origin and target of a key move), for
+     * publishing puzzles. Use [ChessGame::ascii_board] with
+     * [BoardPrintOptions::border] set to choose the orientation and whether
+     * to show coordinates.
+     *
+     * ```text
+     *   +------------------------+
+     * 8 | r  n  b  q  k  b  n  r |
+     * 7 | p  p  p  p  p  p  p  p |
+     * 6 | .  .  .  .  .  .  .  . |
+     * 5 | .  .  .  .  .  .  .  . |
+     * 4 | .  .  .  . [.] .  .  . |
+     * 3 | .  .  .  .  .  .  .  . |
+     * 2 | P  P  P  P [P] P  P  P |
+     * 1 | R  N  B  Q  K  B  N  R |
+     *   +------------------------+
+     *     a  b  c  d  e  f  g  h
+     * ```
+     */
+    pub fn diagram(&self, highlight: &[usize]) -> String {
+        return self.ascii_board(BoardPrintOptions {
+            highlights: highlight.iter().filter(|i| **i < 64).fold(0, |mask, i| mask | 1 << i),
+            border: true,
+            ..Default::default()
+        });
+    }
+
+    /**
+     * Renders the board from white's side with coordinates, the same as
+     * [ChessGame::ascii_board] with the default [BoardPrintOptions].
+     */
+    pub fn ascii(&self) -> String {
+        return self.ascii_board(BoardPrintOptions::default());
+    }
+
+    /**
+     * Renders the board from white's side with coordinates like
+     * [ChessGame::ascii], but with the chess symbols from Unicode. Empty
+     * light squares are `□` and empty dark squares `■`, with `a1` dark.
+     *
+     * ```text
+     * 8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜
+     * 7  ♟  ♟  ♟  ♟  ♟  ♟  ♟  ♟
+     * 6  □  ■  □  ■  □  ■  □  ■
+     * 5  ■  □  ■  □  ■  □  ■  □
+     * 4  □  ■  □  ■  □  ■  □  ■
+     * 3  ■  □  ■  □  ■  □  ■  □
+     * 2  ♙  ♙  ♙  ♙  ♙  ♙  ♙  ♙
+     * 1  ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖
+     *    a  b  c  d  e  f  g  h
+     * ```
+     */
+    pub fn unicode(&self) -> String {
+        return self.render_board(BoardPrintOptions::default(), |i| {
+            let dark = (i % 8 + i / 8) % 2 == 0;
+            return self.board[i].glyph().unwrap_or(if dark { '■' } else { '□' });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ascii_board() {
+        let game = ChessGame::new();
+        assert_eq!(game.ascii_board(BoardPrintOptions::default()), "\
+8  r  n  b  q  k  b  n  r
+7  p  p  p  p  p  p  p  p
+6  .  .  .  .  .  .  .  .
+5  .  .  .  .  .  .  .  .
+4  .  .  .  .  .  .  .  .
+3  .  .  .  .  .  .  .  .
+2  P  P  P  P  P  P  P  P
+1  R  N  B  Q  K  B  N  R
+   a  b  c  d  e  f  g  h
+");
+
+        let options = BoardPrintOptions {
+            bottom: ChessColor::Bl,
+            coordinates: false,
+            highlights: 1 << 12 | 1 << 28,
+            border: false,
+        };
+        assert_eq!(game.ascii_board(options), concat!(
+            " R  N  B  K  Q  B  N  R\n",
+            " P  P  P [P] P  P  P  P\n",
+            " .  .  .  .  .  .  .  .\n",
+            " .  .  . [.] .  .  .  .\n",
+            " .  .  .  .  .  .  .  .\n",
+            " .  .  .  .  .  .  .  .\n",
+            " p  p  p  p  p  p  p  p\n",
+            " r  n  b  k  q  b  n  r\n",
+        ));
+    }
+
+    #[test]
+    fn diagram() {
+        let game = ChessGame::new();
+        assert_eq!(game.diagram(&[12, 28, 64]), concat!(
+            "  +------------------------+\n",
+            "8 | r  n  b  q  k  b  n  r |\n",
+            "7 | p  p  p  p  p  p  p  p |\n",
+            "6 | .  .  .  .  .  .  .  . |\n",
+            "5 | .  .  .  .  .  .  .  . |\n",
+            "4 | .  .  .  . [.] .  .  . |\n",
+            "3 | .  .  .  .  .  .  .  . |\n",
+            "2 | P  P  P  P [P] P  P  P |\n",
+            "1 | R  N  B  Q  K  B  N  R |\n",
+            "  +------------------------+\n",
+            "    a  b  c  d  e  f  g  h\n",
+        ));
+
+        let options = BoardPrintOptions {
+            bottom: ChessColor::Bl,
+            coordinates: false,
+            highlights: 1 << 4,
+            border: true,
+        };
+        assert!(game.ascii_board(options).starts_with(concat!(
+            "+------------------------+\n",
+            "| R  N  B [K] Q  B  N  R |\n",
+        )));
+    }
+
+    #[test]
+    fn ascii_and_unicode() {
+        let mut game = ChessGame::new();
+        assert_eq!(game.ascii(), game.ascii_board(BoardPrintOptions::default()));
+
+        game.make_move(&game.parse_san("e4").unwrap()).unwrap();
+        assert_eq!(game.unicode(), concat!(
+            "8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜\n",
+            "7  ♟  ♟  ♟  ♟  ♟  ♟  ♟  ♟\n",
+            "6  □  ■  □  ■  □  ■  □  ■\n",
+            "5  ■  □  ■  □  ■  □  ■  □\n",
+            "4  □  ■  □  ■  ♙  ■  □  ■\n",
+            "3  ■  □  ■  □  ■  □  ■  □\n",
+            "2  ♙  ♙  ♙  ♙  □  ♙  ♙  ♙\n",
+            "1  ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖\n",
+            "   a  b  c  d  e  f  g  h\n",
+        ));
+    }
+}
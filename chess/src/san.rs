@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+
+use crate::square::square_name;
+use crate::{ChessColor, ChessGame, ChessMove, ChessPiece, MoveError};
+
+impl ChessMove {
+    /**
+     * Returns the move in Standard Algebraic Notation as played in `game`,
+     * see [ChessGame::to_san].
+     */
+    pub fn san(&self, game: &ChessGame) -> String {
+        return game.to_san(self);
+    }
+}
+
+impl ChessGame {
+    /**
+     * Returns `mv` in Standard Algebraic Notation, e.g. `Nf3`, `exd5`,
+     * `Rad1`, `e8=Q+` or `O-O-O#`, followed by the annotation glyph if there
+     * is one. The move must be legal in the current position (for the color
+     * of the moving piece), which is needed both to disambiguate between
+     * pieces that can reach the same square and to find checks.
+     */
+    pub fn to_san(&self, mv: &ChessMove) -> String {
+        let mut out = self.san_body(mv);
+
+        if let Some(col) = mv.piece.color() {
+            let mut after = self.clone();
+            if after.apply_move(mv).is_ok() {
+                out.extend(after.check_suffix(col.opposite()));
+            }
+        }
+
+        if let Some(annotation) = mv.annotation {
+            out.push_str(&annotation.symbol());
+        }
+
+        return out;
+    }
+
+    /**
+     * Makes the move `mv` like [ChessGame::make_move] and returns it in
+     * Standard Algebraic Notation like [ChessGame::to_san]. The move itself
+     * is written against the position before the move, for disambiguation,
+     * and the check or mate suffix is taken from the position after it.
+     */
+    pub fn apply_move_san_record(&mut self, mv: &ChessMove) -> Result<String, MoveError> {
+        let mut out = self.san_body(mv);
+        self.make_move(mv)?;
+        out.extend(self.check_suffix(self.turn));
+
+        if let Some(annotation) = mv.annotation {
+            out.push_str(&annotation.symbol());
+        }
+
+        return Ok(out);
+    }
+
+    /* '#' if `side` is mated, '+' if it is in check */
+    fn check_suffix(&self, side: ChessColor) -> Option<char> {
+        if !self.is_check_for(side) {
+            return None;
+        }
+        return Some(if self.next_moves[side.index()].is_empty() { '#' } else { '+' });
+    }
+
+    /* everything except the check suffix and annotation */
+    fn san_body(&self, mv: &ChessMove) -> String {
+        if mv.castles {
+            return String::from(if mv.castles_queenside() { "O-O-O" } else { "O-O" });
+        }
+        return self.san_move(mv);
+    }
+
+    /* everything except castling and the check suffix */
+    fn san_move(&self, mv: &ChessMove) -> String {
+        let mut out = mv.piece.str();
+        let origin = square_name(mv.origin);
+
+        if mv.piece.is_pawn() {
+            if mv.captures != ChessPiece::None {
+                out.push_str(&origin[..1]);
+            }
+        } else if let Some(col) = mv.piece.color() {
+            /* other pieces of the same kind that can reach the target */
+            let others: Vec<usize> = self.next_moves[col.index()]
+                                         .iter()
+                                         .filter(|x| x.piece == mv.piece
+                                                 && x.target == mv.target
+                                                 && x.origin != mv.origin)
+                                         .map(|x| x.origin)
+                                         .collect();
+            if !others.is_empty() {
+                if others.iter().all(|x| x % 8 != mv.origin % 8) {
+                    out.push_str(&origin[..1]);
+                } else if others.iter().all(|x| x / 8 != mv.origin / 8) {
+                    out.push_str(&origin[1..]);
+                } else {
+                    out.push_str(&origin);
+                }
+            }
+        }
+
+        if mv.captures != ChessPiece::None {
+            out.push('x');
+        }
+        out.push_str(&square_name(mv.target));
+        if mv.promotes != ChessPiece::None {
+            out.push('=');
+            out.push_str(&mv.promotes.str());
+        }
+
+        return out;
+    }
+
+    /**
+     * Gets the legal moves of the side to move paired with their Standard
+     * Algebraic Notation (see [ChessGame::to_san]), in the order of
+     * [ChessGame::legal_moves_sorted].
+     */
+    pub fn annotated_legal_moves(&self) -> Vec<(ChessMove, String)> {
+        return self.legal_moves_sorted(self.side_to_move())
+                   .into_iter()
+                   .map(|mv| (mv, self.to_san(&mv)))
+                   .collect();
+    }
+
+    /**
+     * Maps the Standard Algebraic Notation of every legal move of the side
+     * to move (see [ChessGame::annotated_legal_moves]) to the move, for
+     * looking up typed moves. Keys include the check or mate suffix.
+     */
+    pub fn san_to_move_map(&self) -> HashMap<String, ChessMove> {
+        let mut out = HashMap::new();
+        for (mv, san) in self.annotated_legal_moves() {
+            let previous = out.insert(san, mv);
+            /* only possible if disambiguation is broken */
+            debug_assert!(previous.is_none(), "two moves with the same SAN as {mv:?}");
+        }
+        return out;
+    }
+
+    /**
+     * Returns every move in [ChessGame::move_history] in Standard Algebraic
+     * Notation, see [ChessGame::to_san]. Each move is converted in the
+     * position it was played in.
+     */
+    pub fn san_moves(&self) -> Vec<String> {
+        let mut game = self.clone();
+        return self.history.iter().map(|entry| {
+            game.restore(&entry.position);
+            return game.to_san(&entry.mv);
+        }).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn annotated_legal_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        let moves = game.annotated_legal_moves();
+        assert_eq!(moves.len(), 20);
+        assert_eq!(moves[0], (ChessMove::to(N(Wh), 1, 16), "Na3".to_string()));
+        assert!(moves.iter().all(|(mv, san)| *san == game.to_san(mv)));
+        assert_eq!(moves.iter().map(|(mv, _)| *mv).collect::<Vec<_>>(),
+                   game.legal_moves_sorted(Wh));
+    }
+
+    #[test]
+    fn san_to_move_map() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let game = ChessGame::new();
+        let map = game.san_to_move_map();
+        assert_eq!(map.len(), 20);
+        assert_eq!(map["e4"], ChessMove::to(P(Wh), 12, 28));
+        assert_eq!(map["Nf3"], ChessMove::to(N(Wh), 6, 21));
+        assert!(!map.contains_key("Ng3"));
+
+        /* both rooks can reach d1 and the queen gives check */
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None, None, None, None,  None, R(Wh), K(Wh),
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, None,  None, None,  Q(Wh),
+            None,  None, None, None, None,  None, None,  None,
+            None,  None, None, None, K(Bl), None, None,  None,
+        ]);
+        let map = game.san_to_move_map();
+        assert_eq!(map.len(), game.get_legal_moves(&Wh).len());
+        assert_eq!(map["Rad1"], ChessMove::to(R(Wh), 0, 3));
+        assert_eq!(map["Rgd1"], ChessMove::to(R(Wh), 6, 3));
+        assert_eq!(map["Qe6+"], ChessMove::to(Q(Wh), 47, 44));
+        assert!(!map.contains_key("Qe6"));
+    }
+
+    #[test]
+    fn san_moves() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let moves = [
+            ChessMove::to(P(Wh), 12, 28), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(N(Wh), 6, 21), ChessMove::to(N(Bl), 57, 42),
+            ChessMove::to(B(Wh), 5, 33), ChessMove::to(P(Bl), 48, 40),
+            ChessMove::captures(B(Wh), 33, 42, N(Bl)), ChessMove::captures(P(Bl), 51, 42, B(Wh)),
+            game.mv_castle(&Wh, false), ChessMove::to(B(Bl), 61, 43),
+            ChessMove::to(P(Wh), 11, 27), ChessMove::to(Q(Bl), 59, 52),
+            ChessMove::captures(P(Wh), 27, 36, P(Bl)), ChessMove::captures(B(Bl), 43, 36, P(Wh)),
+            ChessMove::captures(N(Wh), 21, 36, B(Bl)), ChessMove::captures(Q(Bl), 52, 36, N(Wh)),
+        ];
+        for mv in moves.iter() {
+            assert!(game.get_legal_moves(&game.turn).contains(mv));
+            game.apply_move(mv).unwrap();
+            game.switch_turn();
+        }
+
+        assert_eq!(game.move_history(), moves);
+        assert_eq!(game.san_moves(), [
+            "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O", "Bd6",
+            "d4", "Qe7", "dxe5", "Bxe5", "Nxe5", "Qxe5",
+        ]);
+    }
+
+    #[test]
+    fn disambiguation() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None,  None,  None, K(Wh), None,  None, None,
+            None,  N(Wh), None,  None, None,  None,  None, None,
+            None,  None,  None,  None, None,  None,  None, None,
+            None,  None,  None,  None, None,  N(Wh), None, None,
+            None,  None,  None,  None, None,  None,  None, None,
+            None,  None,  K(Bl), None, None,  None,  None, None,
+            None,  None,  None,  None, None,  None,  None, None,
+            R(Wh), None,  None,  None, None,  None,  None, R(Wh),
+        ]);
+
+        assert_eq!(game.to_san(&ChessMove::to(N(Wh), 9, 19)), "Nbd3");
+        assert_eq!(game.to_san(&ChessMove::to(N(Wh), 29, 19)), "Nfd3");
+        assert_eq!(game.to_san(&ChessMove::to(N(Wh), 29, 39)), "Nh5");
+        assert_eq!(game.to_san(&ChessMove::to(R(Wh), 0, 24)), "R1a4");
+        assert_eq!(game.to_san(&ChessMove::to(R(Wh), 56, 59)), "Rad8");
+        assert_eq!(game.to_san(&ChessMove::to(R(Wh), 63, 61)), "Rhf8");
+    }
+
+    #[test]
+    fn move_san() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        assert_eq!(ChessMove::to(P(Wh), 12, 28).san(&game), "e4");
+        assert_eq!(ChessMove::to(N(Wh), 6, 21).san(&game), "Nf3");
+        for mv in ["e4", "d5", "Nf3", "Nc6", "Bc4", "Bg4"] {
+            game.make_move(&game.parse_san(mv).unwrap()).unwrap();
+        }
+        assert_eq!(ChessMove::captures(P(Wh), 28, 35, P(Bl)).san(&game), "exd5");
+        assert_eq!(game.castling_move(Wh, true).unwrap().san(&game), "O-O");
+        assert_eq!(ChessMove::captures(B(Wh), 26, 53, P(Bl)).san(&game), "Bxf7+");
+    }
+
+    #[test]
+    fn mate_and_promotion() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        game.load_board([
+            None, None, None,  K(Bl), None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  R(Wh), None, None,
+            None, None, None,  None,  R(Wh), None,  None, None,
+            None, None, R(Wh), None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  None, None,
+            None, None, None,  None,  None,  None,  P(Wh), None,
+            None, None, None,  None,  K(Wh), B(Bl), None, None,
+        ]);
+
+        assert_eq!(game.to_san(&ChessMove::to(R(Wh), 21, 19)), "Rd3#");
+        assert_eq!(game.to_san(&ChessMove::to(R(Wh), 21, 13)), "Rf2");
+
+        let mut mv = ChessMove::captures(P(Wh), 54, 61, B(Bl));
+        mv.promotes = Q(Wh);
+        assert_eq!(game.to_san(&mv), "gxf8=Q");
+        mv.promotes = N(Wh);
+        assert_eq!(game.to_san(&mv.annotated(MoveAnnotation::Good)), "gxf8=N!");
+    }
+
+    #[test]
+    fn apply_move_san_record() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let mut record = Vec::new();
+        for mv in [
+            ChessMove::to(P(Wh), 13, 21), ChessMove::to(P(Bl), 52, 36),
+            ChessMove::to(P(Wh), 14, 30), ChessMove::to(Q(Bl), 59, 31),
+        ] {
+            record.push(game.apply_move_san_record(&mv).unwrap());
+        }
+        assert_eq!(record, ["f3", "e5", "g4", "Qh4#"]);
+        assert!(game.is_checkmate());
+
+        /* rejected moves leave the game alone */
+        let mut game = ChessGame::new();
+        assert_eq!(game.apply_move_san_record(&ChessMove::to(P(Bl), 52, 36)),
+                   Err(MoveError::NotYourTurn));
+        assert_eq!(game.turn(), Wh);
+        assert!(game.move_history().is_empty());
+
+        /* both knights can reach d2, the check comes from the position after */
+        game.load_board([
+            None, N(Wh), None, None, None, None,  None, None,
+            None, None,  None, None, None, None,  None, None,
+            None, None,  None, None, None, N(Wh), None, None,
+            None, None,  None, None, None, None,  None, None,
+            None, None,  None, None, None, None,  None, None,
+            None, None,  None, None, None, None,  None, None,
+            None, None,  None, None, None, None,  None, None,
+            K(Wh), None, None, None, K(Bl), None, None, None,
+        ]);
+        assert_eq!(game.apply_move_san_record(&ChessMove::to(N(Wh), 1, 11)),
+                   Ok("Nbd2".to_string()));
+        assert_eq!(game.apply_move_san_record(&ChessMove::to(K(Bl), 60, 51)),
+                   Ok("Kd7".to_string()));
+        assert_eq!(game.apply_move_san_record(&ChessMove::to(N(Wh), 21, 36)),
+                   Ok("Ne5+".to_string()));
+    }
+}
@@ -0,0 +1,250 @@
+use std::cmp::Reverse;
+
+use crate::{Bound, ChessGame, ChessMove, ChessPiece, TranspositionTable, TtEntry};
+
+/* the score for being checkmated on the spot, mates further away score
+ * closer to zero so the search prefers the fastest mate */
+const MATE_SCORE: i32 = 1_000_000;
+
+/* the number of entries in the table [best_move] searches with */
+const TABLE_CAPACITY: usize = 1 << 14;
+
+/* captures of valuable pieces by cheap ones first, then promotions, then
+ * everything else */
+fn move_order_key(mv: &ChessMove) -> i32 {
+    let mut key = mv.promotes.value();
+    if mv.captures != ChessPiece::None {
+        key += 10*mv.captures.value() - mv.piece.value();
+    }
+    return key;
+}
+
+/* the legal moves of the side to move, in the order they are searched, with
+ * `first` (the best move found earlier, if any) ahead of the rest */
+fn ordered_moves(game: &ChessGame, first: Option<ChessMove>) -> Vec<ChessMove> {
+    let mut moves = game.get_legal_moves(&game.turn);
+    moves.sort_by_key(|mv| Reverse(move_order_key(mv)));
+    if let Some(i) = first.and_then(|first| moves.iter().position(|mv| *mv == first)) {
+        let mv = moves.remove(i);
+        moves.insert(0, mv);
+    }
+    return moves;
+}
+
+/* Mate scores count plies from the root, but a position can be reached at
+ * any ply. The table stores them counting from the position itself, and
+ * they are converted back when read. */
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    return if score > MATE_SCORE / 2 {
+        score + ply
+    } else if score < -MATE_SCORE / 2 {
+        score - ply
+    } else {
+        score
+    };
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    return if score > MATE_SCORE / 2 {
+        score - ply
+    } else if score < -MATE_SCORE / 2 {
+        score + ply
+    } else {
+        score
+    };
+}
+
+/* the score of the position for the side to move, searching `depth` more
+ * plies with alpha-beta pruning. `ply` counts the plies from the root, so
+ * that mates found sooner score better. Results searched at least as deep
+ * are reused from `table` where their bound allows, and every result is
+ * stored in it */
+fn negamax(game: &mut ChessGame, depth: u32, ply: i32, mut alpha: i32, beta: i32,
+           mut table: Option<&mut TranspositionTable>) -> i32 {
+    if !game.has_legal_move(game.turn) {
+        return if game.is_check() { ply - MATE_SCORE } else { 0 };
+    }
+    if game.is_draw() {
+        return 0;
+    }
+    if depth == 0 {
+        return game.evaluate_for(game.turn);
+    }
+
+    let hash = game.zobrist_hash();
+    let mut first = None;
+    if let Some(entry) = table.as_deref().and_then(|table| table.probe(hash)) {
+        first = entry.best_move;
+        let score = score_from_table(entry.score, ply);
+        let usable = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => score >= beta,
+            Bound::Upper => score <= alpha,
+        };
+        if entry.depth >= depth && usable {
+            return score;
+        }
+    }
+
+    let original_alpha = alpha;
+    let mut best = -MATE_SCORE;
+    let mut best_move = None;
+    for mv in ordered_moves(game, first) {
+        game.apply_move_internal(&mv);
+        game.switch_turn();
+        let score = -negamax(game, depth - 1, ply + 1, -beta, -alpha, table.as_deref_mut());
+        game.undo_move();
+
+        if score > best {
+            best = score;
+            best_move = Some(mv);
+        }
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    if let Some(table) = table {
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        table.store(TtEntry { hash, best_move, depth, score: score_to_table(best, ply), bound });
+    }
+    return best;
+}
+
+/* the best move searching `depth` plies, with or without a table */
+fn search_root(game: &ChessGame, depth: u32, mut table: Option<&mut TranspositionTable>)
+               -> Option<ChessMove> {
+    if game.is_ended() {
+        return None;
+    }
+    let mut game = game.with_side_to_move();
+    let depth = depth.max(1);
+
+    let mut best = None;
+    let mut alpha = -MATE_SCORE;
+    for mv in ordered_moves(&game, None) {
+        game.apply_move_internal(&mv);
+        game.switch_turn();
+        let score = -negamax(&mut game, depth - 1, 1, -MATE_SCORE, -alpha, table.as_deref_mut());
+        game.undo_move();
+
+        if best.is_none() || score > alpha {
+            best = Some(mv);
+            alpha = score;
+        }
+    }
+    return best;
+}
+
+/**
+ * Searches `depth` plies ahead (at least one) from the position in `game`
+ * and returns the best move for the side to move, or [None] if the game is
+ * over (see [ChessGame::is_ended]). This is a plain negamax search with
+ * alpha-beta pruning that tries captures first, scoring positions at the
+ * end with [ChessGame::evaluate_for] (material, mobility and a few
+ * positional terms). Checkmates and draws are recognized along the way, and
+ * faster mates are preferred. Positions reached more than once are looked
+ * up in a [TranspositionTable] instead of being searched again.
+ *
+ * The search time grows quickly with `depth`, depths up to about 4 answer
+ * within a few seconds in most positions. When several moves score the
+ * same the first one in search order is returned.
+ */
+pub fn best_move(game: &ChessGame, depth: u32) -> Option<ChessMove> {
+    return search_root(game, depth, Some(&mut TranspositionTable::new(TABLE_CAPACITY)));
+}
+
+/**
+ * Like [best_move], but searches with `table`, e.g. to keep the results of
+ * one search for the next move of the same game. The table can be of any
+ * capacity and may already hold entries from other searches.
+ */
+pub fn best_move_with_table(game: &ChessGame, depth: u32, table: &mut TranspositionTable)
+                            -> Option<ChessMove> {
+    return search_root(game, depth, Some(table));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn best_move() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        assert!(search::best_move(&ChessGame::new(), 2).is_some());
+
+        /* back rank mate in one, even though taking the knight wins
+         * material */
+        let mut game = ChessGame::new();
+        game.load_board([
+            R(Wh), None, None, N(Bl), None, None, None, K(Wh),
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, None, None, None,
+            None,  None, None, None,  None, P(Bl), P(Bl), P(Bl),
+            None,  None, None, None,  None, None, K(Bl), None,
+        ]);
+        let mv = search::best_move(&game, 1).unwrap();
+        assert_eq!((mv.origin, mv.target), (0, 56));
+        assert_eq!(search::best_move(&game, 3), Some(mv));
+
+        /* a free queen is taken */
+        game.load_board([
+            None,  None, None, None, None,  None, None, K(Wh),
+            None,  None, None, None, N(Wh), None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, Q(Bl), None, None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, None,
+            None,  None, None, None, None,  None, None, K(Bl),
+        ]);
+        let mv = search::best_move(&game, 2).unwrap();
+        assert_eq!((mv.origin, mv.target, mv.captures), (12, 27, Q(Bl)));
+
+        /* checkmated and stalemated sides have no move to make */
+        let mated = ChessGame::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(search::best_move(&mated, 3), Option::None);
+        let stalemated = ChessGame::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(search::best_move(&stalemated, 3), Option::None);
+
+        /* neither are drawn games with moves left */
+        let fifty = ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert!(fifty.has_legal_move(Wh));
+        assert_eq!(search::best_move(&fifty, 2), Option::None);
+        let bare_kings = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(search::best_move(&bare_kings, 2), Option::None);
+    }
+
+    #[test]
+    fn transposition_table() {
+        /* the table only saves work, the move found is the same */
+        for (fen, depth) in [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 3),
+            ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2),
+            ("6k1/5ppp/8/8/8/8/8/R6K w - - 0 1", 3),
+            ("4k3/8/4K3/8/8/8/8/7Q w - - 0 1", 4),
+        ] {
+            let game = ChessGame::from_fen(fen).unwrap();
+            let without = search::search_root(&game, depth, Option::None);
+            assert_eq!(search::best_move(&game, depth), without, "{fen}");
+
+            let mut table = TranspositionTable::new(1024);
+            assert_eq!(search::best_move_with_table(&game, depth, &mut table), without);
+            assert!(!table.is_empty());
+            /* and reusing a filled table doesn't change it either */
+            assert_eq!(search::best_move_with_table(&game, depth, &mut table), without);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{ChessGame, ChessMove, HistoryEntry};
+
+/* a game as it is serialized: the current position and every move played
+ * with the position it was played in, all positions as FEN strings */
+#[derive(Serialize, Deserialize)]
+struct SerializedGame {
+    fen: String,
+    history: Vec<SerializedMove>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedMove {
+    fen: String,
+    mv: ChessMove,
+}
+
+/* the game with `fen`, or a deserialization error saying what was wrong */
+fn game_from_fen<E: Error>(fen: &str) -> Result<ChessGame, E> {
+    return ChessGame::from_fen(fen).map_err(|error| {
+        E::custom(format!("invalid FEN {fen:?}: {error:?}"))
+    });
+}
+
+/**
+ * Serializes the current position as a FEN string (see
+ * [ChessGame::to_fen]) together with the move history, where every move is
+ * stored with the FEN string of the position it was played in. This keeps
+ * the history usable for [ChessGame::undo_move] and repetition detection
+ * after deserializing.
+ */
+impl Serialize for ChessGame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut game = self.clone();
+        let history = self.history.iter().map(|entry| {
+            game.restore(&entry.position);
+            return SerializedMove { fen: game.to_fen(), mv: entry.mv };
+        }).collect();
+
+        return SerializedGame { fen: self.to_fen(), history }.serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for ChessGame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ChessGame, D::Error> {
+        let serialized = SerializedGame::deserialize(deserializer)?;
+
+        let mut game = game_from_fen::<D::Error>(&serialized.fen)?;
+        for entry in serialized.history.iter() {
+            let position = game_from_fen::<D::Error>(&entry.fen)?.position();
+            game.history.push(HistoryEntry { mv: entry.mv, position });
+        }
+        return Ok(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn serde_round_trip() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mv = ChessMove::to(P(Wh), 12, 28).annotated(MoveAnnotation::Good);
+        let json = serde_json::to_string(&mv).unwrap();
+        let restored: ChessMove = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, mv);
+        assert_eq!(restored.annotation, Some(MoveAnnotation::Good));
+
+        let mut game = ChessGame::new();
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5"] {
+            game.make_move(&game.parse_san(san).unwrap()).unwrap();
+        }
+        let json = serde_json::to_string(&game).unwrap();
+        let mut restored: ChessGame = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_fen(), game.to_fen());
+        assert_eq!(restored.move_history(), game.move_history());
+        assert_eq!(restored.get_legal_moves(&Wh), game.get_legal_moves(&Wh));
+        while let Some(mv) = game.undo_move() {
+            assert_eq!(restored.undo_move(), Some(mv));
+            assert_eq!(restored.to_fen(), game.to_fen());
+        }
+
+        let broken = json.replacen("rnb1kbnr", "rnb1kbnrr", 1);
+        assert!(serde_json::from_str::<ChessGame>(&broken).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+/**
+ * Returns the board index of the square on `file` (`'a'` to `'h'`) and
+ * `rank` (1 to 8), e.g. 28 for `('e', 4)`, or [None] if either is out of
+ * range. Index 0 is `a1` and the board follows rank-major order up to `h8`
+ * (63), see [crate::ChessGame::get_board].
+ */
+pub fn square_to_index(file: char, rank: u8) -> Option<usize> {
+    if !('a'..='h').contains(&file) || !(1..=8).contains(&rank) {
+        return None;
+    }
+    return Some(8*(rank as usize - 1) + (file as usize - 'a' as usize));
+}
+
+/**
+ * Returns the file and rank of the square with board index `i`, e.g.
+ * `('e', 4)` for 28. The inverse of [square_to_index].
+ *
+ * Panics if `i` is not on the board, i.e. not below 64.
+ */
+pub fn index_to_square(i: usize) -> (char, u8) {
+    assert!(i < 64, "square index {i} is off the board");
+    return (char::from(b'a' + (i % 8) as u8), (i / 8 + 1) as u8);
+}
+
+/* e.g. "e4" for square 28 */
+pub(crate) fn square_name(i: usize) -> String {
+    let (file, rank) = index_to_square(i);
+    return format!("{file}{rank}");
+}
+
+/* e.g. 28 for "e4", [None] unless `s` is exactly a square name */
+pub(crate) fn parse_square(s: &str) -> Option<usize> {
+    let mut chars = s.chars();
+    return match (chars.next(), chars.next(), chars.next()) {
+        (Some(file), Some(rank @ '1'..='8'), None) => square_to_index(file, rank as u8 - b'0'),
+        _ => None,
+    };
+}
+
+/**
+ * A square on the board, which is always in bounds. Displays as its name,
+ * e.g. `e4`.
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub struct Square(usize);
+
+impl Square {
+    /**
+     * Returns the square with board index `index`, or [None] if it is not
+     * below 64.
+     */
+    pub fn new(index: usize) -> Option<Square> {
+        return if index < 64 { Some(Square(index)) } else { None };
+    }
+
+    /**
+     * Returns the square on `file` and `rank`, see [square_to_index].
+     */
+    pub fn at(file: char, rank: u8) -> Option<Square> {
+        return square_to_index(file, rank).map(Square);
+    }
+
+    /**
+     * Returns the square called `name`, e.g. `e4`, or [None] if `name` is not
+     * exactly the name of a square.
+     */
+    pub fn from_name(name: &str) -> Option<Square> {
+        return parse_square(name).map(Square);
+    }
+
+    /**
+     * Returns the board index of the square.
+     */
+    pub fn index(&self) -> usize {
+        return self.0;
+    }
+
+    /**
+     * Returns the file of the square, `'a'` to `'h'`.
+     */
+    pub fn file(&self) -> char {
+        return index_to_square(self.0).0;
+    }
+
+    /**
+     * Returns the rank of the square, 1 to 8.
+     */
+    pub fn rank(&self) -> u8 {
+        return index_to_square(self.0).1;
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}{}", self.file(), self.rank());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn square_conversions() {
+        assert_eq!(square_to_index('a', 1), Some(0));
+        assert_eq!(square_to_index('e', 4), Some(28));
+        assert_eq!(square_to_index('h', 8), Some(63));
+        assert_eq!(square_to_index('i', 1), None);
+        assert_eq!(square_to_index('a', 0), None);
+        assert_eq!(square_to_index('a', 9), None);
+        assert_eq!(square_to_index('E', 4), None);
+
+        for i in 0..64 {
+            let (file, rank) = index_to_square(i);
+            assert_eq!(square_to_index(file, rank), Some(i));
+
+            let square = Square::new(i).unwrap();
+            assert_eq!(square.index(), i);
+            assert_eq!(Square::at(file, rank), Some(square));
+            assert_eq!(Square::from_name(&square.to_string()), Some(square));
+        }
+
+        let e4 = Square::at('e', 4).unwrap();
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!((e4.file(), e4.rank()), ('e', 4));
+        assert_eq!(Square::new(64), None);
+        for name in ["", "e", "e9", "i4", "e44", "4e", "E4"] {
+            assert_eq!(Square::from_name(name), None, "{name}");
+        }
+    }
+}
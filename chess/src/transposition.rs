@@ -0,0 +1,158 @@
+use crate::ChessMove;
+
+/**
+ * How a stored score relates to the true score of a position, depending on
+ * whether the search that produced it failed high or low.
+ */
+#[derive(Debug,Copy,Clone,Hash,PartialEq,Eq)]
+pub enum Bound {
+    /** The score is exact. */
+    Exact,
+    /** The true score is at least the stored score (fail high). */
+    Lower,
+    /** The true score is at most the stored score (fail low). */
+    Upper,
+}
+
+/**
+ * One entry in a [TranspositionTable].
+ */
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub struct TtEntry {
+    /**
+     * The full hash of the position, see [crate::ChessGame::zobrist_hash].
+     */
+    pub hash: u64,
+    /**
+     * The best move found in the position, if any.
+     */
+    pub best_move: Option<ChessMove>,
+    /**
+     * The remaining search depth the score was computed with.
+     */
+    pub depth: u32,
+    /**
+     * The score of the position for the side to move.
+     */
+    pub score: i32,
+    /**
+     * How [TtEntry::score] relates to the true score.
+     */
+    pub bound: Bound,
+}
+
+/**
+ * A fixed capacity hash table of search results keyed by
+ * [crate::ChessGame::zobrist_hash]. Each hash maps to exactly one slot, and
+ * storing always replaces whatever was in that slot, so memory use never
+ * grows past the capacity given to [TranspositionTable::new].
+ *
+ * [crate::best_move] searches with a table of its own, pass one to
+ * [crate::best_move_with_table] to keep it between searches.
+ */
+#[derive(Debug,Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    used: usize,
+}
+
+impl TranspositionTable {
+    /**
+     * Creates an empty table with room for `capacity` entries (at least one).
+     */
+    pub fn new(capacity: usize) -> TranspositionTable {
+        return TranspositionTable {
+            entries: vec![None; capacity.max(1)],
+            used: 0,
+        };
+    }
+
+    /**
+     * Returns the maximum number of entries the table can hold.
+     */
+    pub fn capacity(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /**
+     * Returns the number of slots currently in use.
+     */
+    pub fn len(&self) -> usize {
+        return self.used;
+    }
+
+    /**
+     * Returns [true] if nothing has been stored.
+     */
+    pub fn is_empty(&self) -> bool {
+        return self.used == 0;
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        return (hash % self.entries.len() as u64) as usize;
+    }
+
+    /**
+     * Looks up the entry for `hash`. Returns [None] if nothing was stored for
+     * this hash, or if it has since been replaced by a colliding entry.
+     */
+    pub fn probe(&self, hash: u64) -> Option<&TtEntry> {
+        return self.entries[self.slot(hash)]
+                   .as_ref()
+                   .filter(|entry| entry.hash == hash);
+    }
+
+    /**
+     * Stores `entry`, replacing any previous entry in its slot.
+     */
+    pub fn store(&mut self, entry: TtEntry) {
+        let slot = self.slot(entry.hash);
+        if self.entries[slot].is_none() {
+            self.used += 1;
+        }
+        self.entries[slot] = Some(entry);
+    }
+
+    /**
+     * Removes every entry while keeping the capacity.
+     */
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.used = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: u64, score: i32) -> TtEntry {
+        return TtEntry { hash, best_move: None, depth: 1, score, bound: Bound::Exact };
+    }
+
+    #[test]
+    fn probe_and_store() {
+        let mut tt = TranspositionTable::new(8);
+        assert!(tt.is_empty());
+        assert_eq!(tt.probe(3), None);
+
+        tt.store(entry(3, 10));
+        assert_eq!(tt.probe(3), Some(&entry(3, 10)));
+        assert_eq!(tt.len(), 1);
+
+        /* same slot, different position */
+        tt.store(entry(11, 20));
+        assert_eq!(tt.probe(3), None);
+        assert_eq!(tt.probe(11), Some(&entry(11, 20)));
+        assert_eq!(tt.len(), 1);
+
+        for hash in 0..100 {
+            tt.store(entry(hash, 0));
+        }
+        assert_eq!(tt.len(), tt.capacity());
+
+        tt.clear();
+        assert!(tt.is_empty());
+        assert_eq!(tt.capacity(), 8);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{ChessColor, ChessGame, ChessPiece};
+
+/* 12 pieces on 64 squares, the side to move, four castling rights and eight
+ * en passant files */
+const PIECE_KEYS: usize = 0;
+const TURN_KEY: usize = 12 * 64;
+const CASTLE_KEYS: usize = TURN_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLE_KEYS + 4;
+const NUM_KEYS: usize = EN_PASSANT_KEYS + 8;
+
+/* splitmix64, generated at compile time so hashes are stable between runs */
+const KEYS: [u64; NUM_KEYS] = {
+    let mut keys = [0u64; NUM_KEYS];
+    let mut state: u64 = 0x5eed_c4e5_5eed_c4e5;
+    let mut i = 0;
+    while i < NUM_KEYS {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+};
+
+fn piece_index(piece: &ChessPiece) -> Option<usize> {
+    return Some(6*(piece.color()?.index()) + piece.kind()? as usize);
+}
+
+impl ChessGame {
+    /**
+     * Returns a Zobrist hash of the current position, covering the board, the
+     * side to move, castling rights and the en passant file. Equal positions
+     * always hash equally, and the keys are fixed so hashes are stable
+     * between runs.
+     */
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = self.position_signature();
+        if self.side_to_move() == ChessColor::Bl {
+            hash ^= KEYS[TURN_KEY];
+        }
+        return hash;
+    }
+
+    /**
+     * Returns the same hash as [ChessGame::zobrist_hash] except that the side
+     * to move is left out, so a position hashes equally no matter who is to
+     * move.
+     *
+     * This must NOT be used for repetition detection or transposition tables,
+     * where positions with different sides to move are different positions.
+     */
+    pub fn position_signature(&self) -> u64 {
+        let mut hash = 0;
+
+        for (i, piece) in self.board.iter().enumerate() {
+            if let Some(p) = piece_index(piece) {
+                hash ^= KEYS[PIECE_KEYS + 64*p + i];
+            }
+        }
+
+        for col in [ChessColor::Wh, ChessColor::Bl] {
+            if self.can_castle_k[col.index()] {
+                hash ^= KEYS[CASTLE_KEYS + 2*(col.index())];
+            }
+            if self.can_castle_q[col.index()] {
+                hash ^= KEYS[CASTLE_KEYS + 2*(col.index()) + 1];
+            }
+        }
+
+        if let Some(target) = self.en_passant_target() {
+            hash ^= KEYS[EN_PASSANT_KEYS + target % 8];
+        }
+
+        return hash;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn transposition() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let start = ChessGame::new();
+        let mut game = ChessGame::new();
+        for mv in [ChessMove::to(N(Wh), 6, 21), ChessMove::to(N(Bl), 62, 45),
+                   ChessMove::to(N(Wh), 21, 6)] {
+            game.apply_move(&mv).unwrap();
+            game.switch_turn();
+            assert_ne!(game.zobrist_hash(), start.zobrist_hash());
+        }
+        game.apply_move(&ChessMove::to(N(Bl), 45, 62)).unwrap();
+        game.switch_turn();
+        assert_eq!(game.zobrist_hash(), start.zobrist_hash());
+
+        game.switch_turn();
+        assert_ne!(game.zobrist_hash(), start.zobrist_hash());
+    }
+
+    #[test]
+    fn castling_and_en_passant() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let hash = game.zobrist_hash();
+        game.set_castle_eligibility(&Wh, true, false);
+        assert_ne!(game.zobrist_hash(), hash);
+
+        let mut game = ChessGame::new();
+        game.apply_move(&ChessMove::to(P(Wh), 12, 28)).unwrap();
+        let mut other = ChessGame::new();
+        other.apply_move(&ChessMove::to(P(Wh), 12, 20)).unwrap();
+        other.apply_move(&ChessMove::to(P(Wh), 20, 28)).unwrap();
+        assert_eq!(game.get_board(), other.get_board());
+        assert_ne!(game.zobrist_hash(), other.zobrist_hash());
+    }
+
+    #[test]
+    fn position_signature() {
+        use ChessPiece::*;
+        use ChessColor::*;
+
+        let mut game = ChessGame::new();
+        let signature = game.position_signature();
+        let hash = game.zobrist_hash();
+        game.switch_turn();
+        assert_eq!(game.position_signature(), signature);
+        assert_ne!(game.zobrist_hash(), hash);
+
+        game.set_castle_eligibility(&Bl, true, false);
+        assert_ne!(game.position_signature(), signature);
+        game.set_castle_eligibility(&Bl, true, true);
+        game.apply_move(&ChessMove::to(N(Bl), 62, 45)).unwrap();
+        assert_ne!(game.position_signature(), signature);
+    }
+}
@@ -1,48 +1,13 @@
+#![allow(clippy::single_match)]
+#![allow(clippy::collapsible_match)]
+
 use chess::*;
 use std::io::{self, Write};
-use itertools::Either;
-
-/**
- * Print a representation of the provided board using ANSI colors to show which
- * side pieces belong to. Blue is white and red is black. Optionally reversed
- * when `rev` is [true].
- */
-fn print_board(board: &[ChessPiece; 64], rev: bool) {
-    use ChessPiece::*;
-    use ChessColor::*;
-
-    fn c(col: &ChessColor) -> String {
-        return if *col == Wh { String::from("\x1b[34m") }
-               else { String::from("\x1b[31m") };
-    }
-
-    let range = if rev {
-        Either::Right((0..8).rev())
-    } else {
-        Either::Left(0..8)
-    };
-    for y in range {
-        print!("{} ", 8-y);
-        for x in 0..8 {
-            match &board[56-y*8 + x] {
-                P(col) => print!("{}P\x1b[m", c(col)),
-                R(col) => print!("{}R\x1b[m", c(col)),
-                N(col) => print!("{}N\x1b[m", c(col)),
-                B(col) => print!("{}B\x1b[m", c(col)),
-                Q(col) => print!("{}Q\x1b[m", c(col)),
-                K(col) => print!("{}K\x1b[m", c(col)),
-                None => print!("."),
-            };
-        }
-        println!();
-    }
-    println!("  abcdefgh");
-}
 
 /**
  * Print moves and their indices
  */
-fn dump_moves(moves: &Vec<ChessMove>) {
+fn dump_moves(moves: &[ChessMove]) {
     for (i, mv) in moves.iter().enumerate() {
         println!("{i}: {}", mv.algebraic());
     }
@@ -52,18 +17,22 @@ fn main() {
     /* create the game */
     let mut game = ChessGame::new();
 
-    /* loop until the game is over */
-    while !game.is_ended() {
+    /* loop until the game is over, which includes draws that would normally
+     * have to be claimed */
+    while !game.is_ended() && !game.is_draw() {
         /* get all legal moves in a Vec */
-        let moves = game.get_legal_moves(&game.turn);
+        let moves = game.get_legal_moves(&game.turn());
 
         /* print moves, the check state, and the board
-         * (reversed on blacks turn) */
+         * (from the point of view of the player to move) */
         dump_moves(&moves);
         if game.is_check() {
             println!("In check!");
         }
-        print_board(game.get_board(), game.turn == ChessColor::Bl);
+        print!("{}", game.ascii_board(BoardPrintOptions {
+            bottom: game.turn(),
+            ..Default::default()
+        }));
 
         /* take input from the user (index into the moves Vec) */
         print!("Move: ");
@@ -74,10 +43,9 @@ fn main() {
         match inp.trim().parse::<usize>() {
             Ok(i) => {
                 if i < moves.len() {
-                    /* apply the specified move */
-                    game.apply_move(&moves[i]);
-                    /* switch turn */
-                    game.switch_turn();
+                    /* play the specified move, which also switches the turn.
+                     * It comes from the legal moves so it can't fail */
+                    game.make_move(&moves[i]).expect("Listed move was illegal");
                 }
             },
             _ => (),
@@ -86,15 +54,19 @@ fn main() {
 
     if game.is_checkmate() {
         /* print the player who made the last move, i.e. the opposite of
-         * `game.turn` */
-        println!("{} checkmate", if game.turn == ChessColor::Wh {
+         * `game.turn()` */
+        println!("{} checkmate", if game.turn() == ChessColor::Wh {
             "Black"
         } else {
             "White"
         });
     } else {
-        /* if the game is over and it was not checkmate it is trivially
-         * stalemate, which could also be checked using `game.is_stalemate()` */
-        println!("Stalemate");
+        /* not checkmate, so the game has to be drawn */
+        println!("Draw by {}", match game.draw_reason() {
+            Some(DrawReason::InsufficientMaterial) => "insufficient material",
+            Some(DrawReason::ThreefoldRepetition) => "threefold repetition",
+            Some(DrawReason::FiftyMoveRule) => "the fifty-move rule",
+            _ => "stalemate",
+        });
     }
 }